@@ -1,9 +1,15 @@
+mod options;
+mod summary;
 mod tuples;
 mod vecs;
 
+pub use summary::{ModuleSummary, Summarize, TensorSummary};
+
 use dfdx::{
     prelude::{Device, Dtype, Gradients, Shape, Tensor, UniqueId},
     shapes::HasShape,
+    tensor::AsArray,
+    tensor_ops::SumTo,
 };
 
 pub trait Module<X> {
@@ -44,8 +50,11 @@ impl<Err: std::fmt::Display> std::fmt::Display for OptimizerUpdateError<Err> {
 impl<Err: std::fmt::Debug + std::fmt::Display> std::error::Error for OptimizerUpdateError<Err> {}
 
 pub trait Optimizer<M, E: Dtype, D: Device<E>>: Sized {
+    /// Updates a single `#[param]`. `name` is its full dotted path (see [VisitTensors]),
+    /// which optimizers can use to apply different settings to different parameters.
     fn update_tensor<S: Shape>(
         &mut self,
+        name: &str,
         t: &mut Tensor<S, E, D>,
         gradients: &Gradients<E, D>,
         missing_tensors: &mut Vec<UniqueId>,
@@ -71,6 +80,12 @@ pub trait Optimizer<M, E: Dtype, D: Device<E>>: Sized {
     }
 }
 
+/// Something (usually an [Optimizer]) with a learning rate that can be changed between updates.
+pub trait HasLearningRate {
+    fn learning_rate(&self) -> f64;
+    fn learning_rate_mut(&mut self) -> &mut f64;
+}
+
 pub trait BuildOnDevice<E: Dtype, D: Device<E>>: Clone {
     type Built: Clone + std::fmt::Debug;
     fn build_on_device(&self, device: &D) -> Self::Built {
@@ -86,6 +101,98 @@ pub trait ResetParams<E: Dtype, D: Device<E>> {
     fn try_reset_params(&mut self) -> Result<(), D::Err>;
 }
 
+/// Switches modules between training and inference behavior, recursing into `#[module]` fields.
+///
+/// Layers that behave differently while training (e.g. `BatchNorm2D` and `Dropout`) store a
+/// `#[training]` flag, and pick the behavior of [Module::try_forward_mut] from it instead of from
+/// the tape of the input. [Module::try_forward] always uses their inference behavior. Built
+/// modules start out in training mode.
+pub trait SetTraining {
+    fn set_training(&mut self, training: bool);
+
+    fn train(&mut self) {
+        self.set_training(true)
+    }
+
+    fn eval(&mut self) {
+        self.set_training(false)
+    }
+}
+
+/// Whether a tensor visited by [VisitTensors] is trained (`#[param]`), or is only
+/// serialized (`#[serialize]`), like the running statistics of `BatchNorm2D`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TensorKind {
+    Param,
+    Buffer,
+}
+
+/// Called by [VisitTensors::try_visit_tensors] on each tensor of a module.
+pub trait TensorVisitor<E: Dtype, D: Device<E>> {
+    fn visit<S: Shape>(
+        &mut self,
+        name: &str,
+        kind: TensorKind,
+        t: &Tensor<S, E, D>,
+    ) -> Result<(), D::Err>;
+}
+
+/// Called by [VisitTensors::try_visit_tensors_mut] on each tensor of a module.
+pub trait TensorVisitorMut<E: Dtype, D: Device<E>> {
+    fn visit_mut<S: Shape>(
+        &mut self,
+        name: &str,
+        kind: TensorKind,
+        t: &mut Tensor<S, E, D>,
+    ) -> Result<(), D::Err>;
+}
+
+/// Walks every `#[param]` and `#[serialize]` tensor of a module, recursing into `#[module]` fields.
+///
+/// Each tensor is passed to the visitor along with its full dotted path prefixed with `location`,
+/// which matches the name it is saved under by [SaveSafeTensors] (e.g. `l1.matmul.weight`).
+///
+/// [UpdateParams], [ZeroGrads] and [ClipGrads] are all implemented on top of this, and custom
+/// walks (counting parameters, casting, etc.) only need a [TensorVisitor].
+pub trait VisitTensors<E: Dtype, D: Device<E>> {
+    fn visit_tensors<V: TensorVisitor<E, D>>(&self, location: &str, visitor: &mut V) {
+        self.try_visit_tensors(location, visitor).unwrap()
+    }
+    fn try_visit_tensors<V: TensorVisitor<E, D>>(
+        &self,
+        location: &str,
+        visitor: &mut V,
+    ) -> Result<(), D::Err>;
+
+    fn visit_tensors_mut<V: TensorVisitorMut<E, D>>(&mut self, location: &str, visitor: &mut V) {
+        self.try_visit_tensors_mut(location, visitor).unwrap()
+    }
+    fn try_visit_tensors_mut<V: TensorVisitorMut<E, D>>(
+        &mut self,
+        location: &str,
+        visitor: &mut V,
+    ) -> Result<(), D::Err>;
+}
+
+/// A bare tensor has no way of knowing it is a `#[param]`, so it is visited as a [TensorKind::Buffer].
+/// The derive reports `#[param]` fields itself.
+impl<S: Shape, E: Dtype, D: Device<E>> VisitTensors<E, D> for Tensor<S, E, D> {
+    fn try_visit_tensors<V: TensorVisitor<E, D>>(
+        &self,
+        location: &str,
+        visitor: &mut V,
+    ) -> Result<(), D::Err> {
+        visitor.visit(location, TensorKind::Buffer, self)
+    }
+    fn try_visit_tensors_mut<V: TensorVisitorMut<E, D>>(
+        &mut self,
+        location: &str,
+        visitor: &mut V,
+    ) -> Result<(), D::Err> {
+        visitor.visit_mut(location, TensorKind::Buffer, self)
+    }
+}
+
 pub trait UpdateParams<E: Dtype, D: Device<E>> {
     fn update_params<M, Optim: Optimizer<M, E, D>>(
         &mut self,
@@ -104,6 +211,51 @@ pub trait UpdateParams<E: Dtype, D: Device<E>> {
     ) -> Result<(), D::Err>;
 }
 
+struct UpdateParamsVisitor<'a, M, Optim, E: Dtype, D: Device<E>> {
+    optimizer: &'a mut Optim,
+    gradients: &'a Gradients<E, D>,
+    missing_tensors: &'a mut Vec<UniqueId>,
+    module: std::marker::PhantomData<M>,
+}
+
+impl<'a, M, Optim: Optimizer<M, E, D>, E: Dtype, D: Device<E>> TensorVisitorMut<E, D>
+    for UpdateParamsVisitor<'a, M, Optim, E, D>
+{
+    fn visit_mut<S: Shape>(
+        &mut self,
+        name: &str,
+        kind: TensorKind,
+        t: &mut Tensor<S, E, D>,
+    ) -> Result<(), D::Err> {
+        match kind {
+            TensorKind::Param => {
+                self.optimizer
+                    .update_tensor(name, t, self.gradients, self.missing_tensors)
+            }
+            TensorKind::Buffer => Ok(()),
+        }
+    }
+}
+
+impl<E: Dtype, D: Device<E>, T: VisitTensors<E, D>> UpdateParams<E, D> for T {
+    fn try_update_params<M, Optim: Optimizer<M, E, D>>(
+        &mut self,
+        optimizer: &mut Optim,
+        gradients: &Gradients<E, D>,
+        missing_tensors: &mut Vec<UniqueId>,
+    ) -> Result<(), D::Err> {
+        self.try_visit_tensors_mut(
+            "",
+            &mut UpdateParamsVisitor {
+                optimizer,
+                gradients,
+                missing_tensors,
+                module: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
 pub trait ZeroGrads<E: Dtype, D: Device<E>> {
     fn zero_grads(&self, grads: &mut Gradients<E, D>) {
         self.try_zero_grads(grads).unwrap()
@@ -121,6 +273,178 @@ pub trait ZeroGrads<E: Dtype, D: Device<E>> {
     }
 }
 
+struct ZeroGradsVisitor<'a, E: Dtype, D: Device<E>>(&'a mut Gradients<E, D>);
+
+impl<'a, E: Dtype, D: Device<E>> TensorVisitor<E, D> for ZeroGradsVisitor<'a, E, D> {
+    fn visit<S: Shape>(
+        &mut self,
+        _: &str,
+        kind: TensorKind,
+        t: &Tensor<S, E, D>,
+    ) -> Result<(), D::Err> {
+        match kind {
+            TensorKind::Param => t.device().try_fill_with_zeros(self.0.get_or_alloc_mut(t)?),
+            TensorKind::Buffer => Ok(()),
+        }
+    }
+}
+
+impl<E: Dtype, D: Device<E>, T: VisitTensors<E, D>> ZeroGrads<E, D> for T {
+    fn try_zero_grads(&self, grads: &mut Gradients<E, D>) -> Result<(), D::Err> {
+        self.try_visit_tensors("", &mut ZeroGradsVisitor(grads))
+    }
+}
+
+pub trait ClipGrads<E: Dtype, D: Device<E>> {
+    /// Adds the squared L2 norm of the gradient of every `#[param]` to `norm_squared`.
+    /// Params without a gradient in `grads` are skipped.
+    fn try_grads_norm_squared(
+        &self,
+        grads: &Gradients<E, D>,
+        norm_squared: &mut E,
+    ) -> Result<(), D::Err>;
+
+    /// Multiplies the gradient of every `#[param]` by `scale` in place.
+    fn try_grads_scale(&self, grads: &mut Gradients<E, D>, scale: E) -> Result<(), D::Err>;
+
+    /// Clamps each value of the gradient of every `#[param]` to `[min, max]` in place.
+    fn try_grads_clamp(&self, grads: &mut Gradients<E, D>, min: E, max: E) -> Result<(), D::Err>;
+
+    /// The global L2 norm of all `#[param]` gradients.
+    fn grads_norm(&self, grads: &Gradients<E, D>) -> E
+    where
+        E: num_traits::Float,
+    {
+        self.try_grads_norm(grads).unwrap()
+    }
+    fn try_grads_norm(&self, grads: &Gradients<E, D>) -> Result<E, D::Err>
+    where
+        E: num_traits::Float,
+    {
+        let mut norm_squared = E::zero();
+        self.try_grads_norm_squared(grads, &mut norm_squared)?;
+        Ok(norm_squared.sqrt())
+    }
+
+    /// Rescales all `#[param]` gradients so their global L2 norm is at most `max_norm`.
+    /// Returns the global norm from before clipping.
+    fn clip_grad_norm(&self, grads: &mut Gradients<E, D>, max_norm: E) -> E
+    where
+        E: num_traits::Float,
+    {
+        self.try_clip_grad_norm(grads, max_norm).unwrap()
+    }
+    fn try_clip_grad_norm(&self, grads: &mut Gradients<E, D>, max_norm: E) -> Result<E, D::Err>
+    where
+        E: num_traits::Float,
+    {
+        let norm = self.try_grads_norm(grads)?;
+        if norm > max_norm {
+            self.try_grads_scale(grads, max_norm / norm)?;
+        }
+        Ok(norm)
+    }
+
+    /// Clamps every `#[param]` gradient value to `[-clip_value, clip_value]`.
+    fn clip_grad_value(&self, grads: &mut Gradients<E, D>, clip_value: E)
+    where
+        E: num_traits::Float,
+    {
+        self.try_clip_grad_value(grads, clip_value).unwrap()
+    }
+    fn try_clip_grad_value(&self, grads: &mut Gradients<E, D>, clip_value: E) -> Result<(), D::Err>
+    where
+        E: num_traits::Float,
+    {
+        self.try_grads_clamp(grads, -clip_value, clip_value)
+    }
+}
+
+struct GradsNormSquaredVisitor<'a, E: Dtype, D: Device<E>> {
+    grads: &'a Gradients<E, D>,
+    norm_squared: &'a mut E,
+}
+
+impl<'a, E: Dtype, D: Device<E>> TensorVisitor<E, D> for GradsNormSquaredVisitor<'a, E, D> {
+    fn visit<S: Shape>(
+        &mut self,
+        _: &str,
+        kind: TensorKind,
+        t: &Tensor<S, E, D>,
+    ) -> Result<(), D::Err> {
+        if kind == TensorKind::Param && self.grads.get_ref_checked(t).is_some() {
+            *self.norm_squared += self.grads.get(t).try_square()?.try_sum::<(), _>()?.array();
+        }
+        Ok(())
+    }
+}
+
+struct GradsMapVisitor<'a, E: Dtype, D: Device<E>, F> {
+    grads: &'a mut Gradients<E, D>,
+    f: F,
+}
+
+impl<'a, E: Dtype, D: Device<E>, F: FnMut(E) -> E> TensorVisitor<E, D>
+    for GradsMapVisitor<'a, E, D, F>
+{
+    fn visit<S: Shape>(
+        &mut self,
+        _: &str,
+        kind: TensorKind,
+        t: &Tensor<S, E, D>,
+    ) -> Result<(), D::Err> {
+        if kind == TensorKind::Param && self.grads.get_ref_checked(t).is_some() {
+            let grad = self.grads.get_or_alloc_mut(t)?;
+            t.device().try_element_map(grad, &mut self.f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<E: Dtype, D: Device<E>, T: VisitTensors<E, D>> ClipGrads<E, D> for T {
+    fn try_grads_norm_squared(
+        &self,
+        grads: &Gradients<E, D>,
+        norm_squared: &mut E,
+    ) -> Result<(), D::Err> {
+        self.try_visit_tensors(
+            "",
+            &mut GradsNormSquaredVisitor {
+                grads,
+                norm_squared,
+            },
+        )
+    }
+
+    fn try_grads_scale(&self, grads: &mut Gradients<E, D>, scale: E) -> Result<(), D::Err> {
+        self.try_visit_tensors(
+            "",
+            &mut GradsMapVisitor {
+                grads,
+                f: |e| e * scale,
+            },
+        )
+    }
+
+    fn try_grads_clamp(&self, grads: &mut Gradients<E, D>, min: E, max: E) -> Result<(), D::Err> {
+        self.try_visit_tensors(
+            "",
+            &mut GradsMapVisitor {
+                grads,
+                f: |e| {
+                    if e < min {
+                        min
+                    } else if e > max {
+                        max
+                    } else {
+                        e
+                    }
+                },
+            },
+        )
+    }
+}
+
 pub trait SaveSafeTensors {
     fn save_safetensors<P: AsRef<std::path::Path>>(
         &self,
@@ -149,6 +473,10 @@ pub trait SaveSafeTensors {
     );
 }
 
+/// Loads tensors by the names [SaveSafeTensors] saves them under, e.g. `l1.matmul.weight`.
+///
+/// Files saved before `#[module]` fields were separated by a `.` (e.g. `l1matmulweight`) are
+/// still loaded by `#[derive(LoadSafeTensors)]`, which falls back to the undotted names.
 pub trait LoadSafeTensors {
     fn load_safetensors<P: AsRef<std::path::Path>>(
         &mut self,
@@ -241,6 +569,41 @@ unit_safetensors!(i64);
 unit_safetensors!(isize);
 unit_safetensors!(usize);
 
+macro_rules! unit_visit_tensors {
+    ($Ty:ty) => {
+        impl<E: Dtype, D: Device<E>> VisitTensors<E, D> for $Ty {
+            fn try_visit_tensors<V: TensorVisitor<E, D>>(
+                &self,
+                _: &str,
+                _: &mut V,
+            ) -> Result<(), D::Err> {
+                Ok(())
+            }
+            fn try_visit_tensors_mut<V: TensorVisitorMut<E, D>>(
+                &mut self,
+                _: &str,
+                _: &mut V,
+            ) -> Result<(), D::Err> {
+                Ok(())
+            }
+        }
+    };
+}
+
+unit_visit_tensors!(bool);
+unit_visit_tensors!(f32);
+unit_visit_tensors!(f64);
+unit_visit_tensors!(u8);
+unit_visit_tensors!(u16);
+unit_visit_tensors!(u32);
+unit_visit_tensors!(u64);
+unit_visit_tensors!(i8);
+unit_visit_tensors!(i16);
+unit_visit_tensors!(i32);
+unit_visit_tensors!(i64);
+unit_visit_tensors!(isize);
+unit_visit_tensors!(usize);
+
 pub trait BuildModuleExt<M>: Sized {
     fn build_module_ext<E: Dtype>(&self, m: M) -> M::Built
     where
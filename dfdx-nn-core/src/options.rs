@@ -0,0 +1,77 @@
+use dfdx::{dtypes::Dtype, tensor_ops::Device};
+
+impl<E: Dtype, D: Device<E>, T: crate::BuildOnDevice<E, D>> crate::BuildOnDevice<E, D>
+    for Option<T>
+{
+    type Built = Option<T::Built>;
+    fn try_build_on_device(&self, device: &D) -> Result<Self::Built, <D>::Err> {
+        self.as_ref()
+            .map(|m| m.try_build_on_device(device))
+            .transpose()
+    }
+}
+
+impl<E: Dtype, D: Device<E>, T: crate::ResetParams<E, D>> crate::ResetParams<E, D> for Option<T> {
+    fn try_reset_params(&mut self) -> Result<(), <D>::Err> {
+        match self {
+            Some(m) => m.try_reset_params(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T: crate::SetTraining> crate::SetTraining for Option<T> {
+    fn set_training(&mut self, training: bool) {
+        if let Some(m) = self {
+            m.set_training(training);
+        }
+    }
+}
+
+impl<E: Dtype, D: Device<E>, T: crate::VisitTensors<E, D>> crate::VisitTensors<E, D> for Option<T> {
+    fn try_visit_tensors<V: crate::TensorVisitor<E, D>>(
+        &self,
+        location: &str,
+        visitor: &mut V,
+    ) -> Result<(), D::Err> {
+        match self {
+            Some(m) => m.try_visit_tensors(location, visitor),
+            None => Ok(()),
+        }
+    }
+    fn try_visit_tensors_mut<V: crate::TensorVisitorMut<E, D>>(
+        &mut self,
+        location: &str,
+        visitor: &mut V,
+    ) -> Result<(), D::Err> {
+        match self {
+            Some(m) => m.try_visit_tensors_mut(location, visitor),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T: crate::SaveSafeTensors> crate::SaveSafeTensors for Option<T> {
+    fn write_safetensors(
+        &self,
+        location: &str,
+        tensors: &mut Vec<(String, safetensors::Dtype, Vec<usize>, Vec<u8>)>,
+    ) {
+        if let Some(m) = self {
+            m.write_safetensors(location, tensors);
+        }
+    }
+}
+
+impl<T: crate::LoadSafeTensors> crate::LoadSafeTensors for Option<T> {
+    fn read_safetensors<'a>(
+        &mut self,
+        location: &str,
+        tensors: &safetensors::SafeTensors<'a>,
+    ) -> Result<(), safetensors::SafeTensorError> {
+        match self {
+            Some(m) => m.read_safetensors(location, tensors),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use dfdx::{
+    prelude::{Device, Dtype, Shape, Tensor},
+    shapes::HasShape,
+};
+
+use crate::{TensorKind, TensorVisitor, VisitTensors};
+
+/// A single tensor of a [ModuleSummary].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TensorSummary {
+    /// Full dotted path, the same as the key used by [crate::SaveSafeTensors].
+    pub name: String,
+    pub kind: TensorKind,
+    pub shape: Vec<usize>,
+    pub dtype: safetensors::Dtype,
+    pub num_elements: usize,
+    pub num_bytes: usize,
+}
+
+/// Every tensor of a module in visiting order, see [Summarize::summary].
+///
+/// The [std::fmt::Display] impl prints a table of all tensors followed by the totals:
+/// ```ignore
+/// let model = dev.build_module::<f32>(arch);
+/// println!("{}", model.summary());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModuleSummary {
+    pub tensors: Vec<TensorSummary>,
+}
+
+impl ModuleSummary {
+    /// Number of trainable (`#[param]`) elements.
+    pub fn num_params(&self) -> usize {
+        self.total(TensorKind::Param, |t| t.num_elements)
+    }
+
+    /// Size in bytes of all trainable (`#[param]`) tensors.
+    pub fn param_bytes(&self) -> usize {
+        self.total(TensorKind::Param, |t| t.num_bytes)
+    }
+
+    /// Number of elements in non-trainable buffers, e.g. `BatchNorm2D::running_mean`.
+    pub fn num_buffer_elements(&self) -> usize {
+        self.total(TensorKind::Buffer, |t| t.num_elements)
+    }
+
+    /// Size in bytes of all non-trainable buffers.
+    pub fn buffer_bytes(&self) -> usize {
+        self.total(TensorKind::Buffer, |t| t.num_bytes)
+    }
+
+    fn total(&self, kind: TensorKind, f: impl Fn(&TensorSummary) -> usize) -> usize {
+        self.tensors.iter().filter(|t| t.kind == kind).map(f).sum()
+    }
+}
+
+impl std::fmt::Display for ModuleSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rows: Vec<[String; 5]> = self
+            .tensors
+            .iter()
+            .map(|t| {
+                let kind = match t.kind {
+                    TensorKind::Param => "param",
+                    TensorKind::Buffer => "buffer",
+                };
+                [
+                    t.name.clone(),
+                    kind.to_string(),
+                    format!("{:?}", t.shape),
+                    format!("{:?}", t.dtype),
+                    t.num_bytes.to_string(),
+                ]
+            })
+            .collect();
+
+        let header = ["name", "kind", "shape", "dtype", "bytes"].map(String::from);
+        let mut widths = header.clone().map(|h| h.len());
+        for row in rows.iter() {
+            for (w, col) in widths.iter_mut().zip(row.iter()) {
+                *w = (*w).max(col.len());
+            }
+        }
+
+        for row in std::iter::once(&header).chain(rows.iter()) {
+            writeln!(
+                f,
+                "{:<w0$}  {:<w1$}  {:<w2$}  {:<w3$}  {:>w4$}",
+                row[0],
+                row[1],
+                row[2],
+                row[3],
+                row[4],
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2],
+                w3 = widths[3],
+                w4 = widths[4],
+            )?;
+        }
+        writeln!(
+            f,
+            "trainable params: {} ({} bytes)",
+            self.num_params(),
+            self.param_bytes()
+        )?;
+        write!(
+            f,
+            "buffers: {} ({} bytes)",
+            self.num_buffer_elements(),
+            self.buffer_bytes()
+        )
+    }
+}
+
+struct SummaryVisitor<'a>(&'a mut ModuleSummary);
+
+impl<'a, E: Dtype, D: Device<E>> TensorVisitor<E, D> for SummaryVisitor<'a> {
+    fn visit<S: Shape>(
+        &mut self,
+        name: &str,
+        kind: TensorKind,
+        t: &Tensor<S, E, D>,
+    ) -> Result<(), D::Err> {
+        let shape: Vec<usize> = t.shape().concrete().into();
+        let num_elements = shape.iter().product();
+        self.0.tensors.push(TensorSummary {
+            name: name.to_string(),
+            kind,
+            shape,
+            dtype: <E as dfdx::dtypes::SafeTensorsDtype>::DTYPE,
+            num_elements,
+            num_bytes: num_elements * std::mem::size_of::<E>(),
+        });
+        Ok(())
+    }
+}
+
+/// Reports the tensors of a built module, implemented for everything that implements [VisitTensors].
+pub trait Summarize<E: Dtype, D: Device<E>>: VisitTensors<E, D> {
+    /// Lists every `#[param]` and `#[serialize]` tensor along with its shape, dtype and size.
+    fn summary(&self) -> ModuleSummary {
+        let mut summary = ModuleSummary::default();
+        self.visit_tensors("", &mut SummaryVisitor(&mut summary));
+        summary
+    }
+
+    /// Number of trainable (`#[param]`) elements.
+    fn num_params(&self) -> usize {
+        self.summary().num_params()
+    }
+}
+
+impl<E: Dtype, D: Device<E>, T: VisitTensors<E, D>> Summarize<E, D> for T {}
@@ -1,4 +1,4 @@
-use dfdx::{dtypes::Dtype, tensor::UniqueId, tensor_ops::Device};
+use dfdx::{dtypes::Dtype, tensor_ops::Device};
 
 macro_rules! tuple_impls {
     ([$($name:ident),+] [$($idx:tt),+], $last:ident, [$($rev_tail:ident),*]) => {
@@ -39,21 +39,27 @@ macro_rules! tuple_impls {
             }
         }
 
-        impl<Dev: Device<Elem>, Elem: Dtype, $($name: crate::UpdateParams<Elem, Dev>),+> crate::UpdateParams<Elem, Dev> for ($($name,)+) {
-            fn try_update_params<M, Optim: crate::Optimizer<M, Elem, Dev>>(
-                &mut self,
-                optimizer: &mut Optim,
-                gradients: &dfdx::prelude::Gradients<Elem, Dev>,
-                missing_tensors: &mut Vec<UniqueId>,
-            ) -> Result<(), Dev::Err> {
-                $(self.$idx.try_update_params(optimizer, gradients, missing_tensors)?;)+
-                Ok(())
+        impl<$($name: crate::SetTraining, )+> crate::SetTraining for ($($name,)+) {
+            fn set_training(&mut self, training: bool) {
+                $(self.$idx.set_training(training);)+
             }
         }
 
-        impl<Dev: Device<Elem>, Elem: Dtype, $($name: crate::ZeroGrads<Elem, Dev>),+> crate::ZeroGrads<Elem, Dev> for ($($name,)+) {
-            fn try_zero_grads(&self, grads: &mut dfdx::prelude::Gradients<Elem, Dev>) -> Result<(), Dev::Err> {
-                $(self.$idx.try_zero_grads(grads)?;)+
+        impl<Dev: Device<Elem>, Elem: Dtype, $($name: crate::VisitTensors<Elem, Dev>),+> crate::VisitTensors<Elem, Dev> for ($($name,)+) {
+            fn try_visit_tensors<V: crate::TensorVisitor<Elem, Dev>>(
+                &self,
+                location: &str,
+                visitor: &mut V,
+            ) -> Result<(), Dev::Err> {
+                $(self.$idx.try_visit_tensors(&format!("{location}{}.", $idx), visitor)?;)+
+                Ok(())
+            }
+            fn try_visit_tensors_mut<V: crate::TensorVisitorMut<Elem, Dev>>(
+                &mut self,
+                location: &str,
+                visitor: &mut V,
+            ) -> Result<(), Dev::Err> {
+                $(self.$idx.try_visit_tensors_mut(&format!("{location}{}.", $idx), visitor)?;)+
                 Ok(())
             }
         }
@@ -1,4 +1,4 @@
-use dfdx::{dtypes::Dtype, tensor::UniqueId, tensor_ops::Device};
+use dfdx::{dtypes::Dtype, tensor_ops::Device};
 
 impl<E: Dtype, D: Device<E>, T: crate::BuildOnDevice<E, D>> crate::BuildOnDevice<E, D> for Vec<T> {
     type Built = Vec<T::Built>;
@@ -18,24 +18,32 @@ impl<E: Dtype, D: Device<E>, T: crate::ResetParams<E, D>> crate::ResetParams<E,
     }
 }
 
-impl<E: Dtype, D: Device<E>, T: crate::UpdateParams<E, D>> crate::UpdateParams<E, D> for Vec<T> {
-    fn try_update_params<M, Optim: crate::Optimizer<M, E, D>>(
-        &mut self,
-        optimizer: &mut Optim,
-        gradients: &dfdx::tensor::Gradients<E, D>,
-        missing_tensors: &mut Vec<UniqueId>,
-    ) -> Result<(), D::Err> {
+impl<T: crate::SetTraining> crate::SetTraining for Vec<T> {
+    fn set_training(&mut self, training: bool) {
         for m_i in self.iter_mut() {
-            m_i.try_update_params(optimizer, gradients, missing_tensors)?;
+            m_i.set_training(training);
         }
-        Ok(())
     }
 }
 
-impl<E: Dtype, D: Device<E>, T: crate::ZeroGrads<E, D>> crate::ZeroGrads<E, D> for Vec<T> {
-    fn try_zero_grads(&self, grads: &mut dfdx::tensor::Gradients<E, D>) -> Result<(), <D>::Err> {
-        for m_i in self.iter() {
-            m_i.try_zero_grads(grads)?;
+impl<E: Dtype, D: Device<E>, T: crate::VisitTensors<E, D>> crate::VisitTensors<E, D> for Vec<T> {
+    fn try_visit_tensors<V: crate::TensorVisitor<E, D>>(
+        &self,
+        location: &str,
+        visitor: &mut V,
+    ) -> Result<(), D::Err> {
+        for (i, m_i) in self.iter().enumerate() {
+            m_i.try_visit_tensors(&format!("{location}{i}."), visitor)?;
+        }
+        Ok(())
+    }
+    fn try_visit_tensors_mut<V: crate::TensorVisitorMut<E, D>>(
+        &mut self,
+        location: &str,
+        visitor: &mut V,
+    ) -> Result<(), D::Err> {
+        for (i, m_i) in self.iter_mut().enumerate() {
+            m_i.try_visit_tensors_mut(&format!("{location}{i}."), visitor)?;
         }
         Ok(())
     }
@@ -8,7 +8,7 @@ macro_rules! has_attr {
     };
 }
 
-#[proc_macro_derive(CustomModule, attributes(module, built))]
+#[proc_macro_derive(CustomModule, attributes(module, built, training))]
 pub fn custom_module(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
@@ -39,6 +39,8 @@ pub fn custom_module(input: proc_macro::TokenStream) -> proc_macro::TokenStream
                                     .predicates
                                     .push(parse_quote!(#ty: dfdx_nn_core::BuildOnDevice<Elem, Dev>));
                                 quote_spanned!(f.span()=> #[module] #[serialize] #vis #name: <#ty as dfdx_nn_core::BuildOnDevice<Elem, Dev>>::Built,)
+                            } else if has_attr!(f, "training") {
+                                quote_spanned!(f.span()=> #[training] #vis #name: #ty,)
                             } else {
                                 quote_spanned!(f.span()=> #vis #name: #ty,)
                             }
@@ -55,6 +57,8 @@ pub fn custom_module(input: proc_macro::TokenStream) -> proc_macro::TokenStream
                                     .predicates
                                     .push(parse_quote!(#ty: dfdx_nn_core::BuildOnDevice<Elem, Dev>));
                                 quote_spanned!(f.span()=> #[module] #[serialize] #vis <#ty as dfdx_nn_core::BuildOnDevice<Elem, Dev>>::Built,)
+                            } else if has_attr!(f, "training") {
+                                quote_spanned!(f.span()=> #[training] #vis #ty,)
                             } else {
                                 quote_spanned!(f.span()=> #vis #ty,)
                             }
@@ -91,12 +95,42 @@ pub fn custom_module(input: proc_macro::TokenStream) -> proc_macro::TokenStream
 
         let def = if has_fields_to_build {
             quote! {
-                #[derive(Clone, Debug, dfdx_nn_derives::ResetParams, dfdx_nn_derives::UpdateParams, dfdx_nn_derives::ZeroGrads, dfdx_nn_derives::SaveSafeTensors, dfdx_nn_derives::LoadSafeTensors)]
+                #[derive(Clone, Debug, dfdx_nn_derives::ResetParams, dfdx_nn_derives::VisitTensors, dfdx_nn_derives::SaveSafeTensors, dfdx_nn_derives::LoadSafeTensors, dfdx_nn_derives::SetTraining)]
                 pub struct #built_name #built_impl #built_where #fields
             }
         } else {
-            // there are no fields to build - we still have to derive ResetParams/UpdateParams/ZeroGrads, but since
-            // there aren't any fields, they will just be passthrough impls
+            // there are no fields to build - we still have to derive ResetParams/VisitTensors, but since
+            // there aren't any fields, they will just be passthrough impls. SetTraining still has to
+            // set any `#[training]` flags.
+            let training_sets = match &input.data {
+                Data::Struct(ref obj) => match obj.fields {
+                    Fields::Named(ref fields) => {
+                        let sets = fields
+                            .named
+                            .iter()
+                            .filter(|f| has_attr!(f, "training"))
+                            .map(|f| {
+                                let name = &f.ident;
+                                quote_spanned!(f.span()=>self.#name = training;)
+                            });
+                        quote! { #(#sets)* }
+                    }
+                    Fields::Unnamed(ref fields) => {
+                        let sets = fields
+                            .unnamed
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, f)| has_attr!(f, "training"))
+                            .map(|(i, f)| {
+                                let index = Index::from(i);
+                                quote_spanned!(f.span()=>self.#index = training;)
+                            });
+                        quote! { #(#sets)* }
+                    }
+                    Fields::Unit => Default::default(),
+                },
+                _ => unreachable!(),
+            };
             let mut build_generics = built_generics.clone();
             if !has_fields_to_build {
                 build_generics
@@ -134,19 +168,25 @@ pub fn custom_module(input: proc_macro::TokenStream) -> proc_macro::TokenStream
                     }
                 }
 
-                impl #build_impl dfdx_nn_core::UpdateParams<Elem, Dev> for #builder_name #built_ty #built_where {
-                    fn try_update_params<M, Optim: dfdx_nn_core::Optimizer<M, Elem, Dev>>(
-                        &mut self,
-                        optimizer: &mut Optim,
-                        gradients: &dfdx::tensor::Gradients<Elem, Dev>,
-                        missing_tensors: &mut Vec<dfdx::tensor::UniqueId>,
-                    ) -> Result<(), Dev::Err> {
-                        Ok(())
+                impl #built_impl dfdx_nn_core::SetTraining for #builder_name #built_ty #built_where {
+                    fn set_training(&mut self, training: bool) {
+                        #training_sets
                     }
                 }
 
-                impl #build_impl dfdx_nn_core::ZeroGrads<Elem, Dev> for #builder_name #built_ty #built_where {
-                    fn try_zero_grads(&self, grads: &mut dfdx::tensor::Gradients<Elem, Dev>) -> Result<(), Dev::Err> {
+                impl #build_impl dfdx_nn_core::VisitTensors<Elem, Dev> for #builder_name #built_ty #built_where {
+                    fn try_visit_tensors<V: dfdx_nn_core::TensorVisitor<Elem, Dev>>(
+                        &self,
+                        location: &str,
+                        visitor: &mut V,
+                    ) -> Result<(), Dev::Err> {
+                        Ok(())
+                    }
+                    fn try_visit_tensors_mut<V: dfdx_nn_core::TensorVisitorMut<Elem, Dev>>(
+                        &mut self,
+                        location: &str,
+                        visitor: &mut V,
+                    ) -> Result<(), Dev::Err> {
                         Ok(())
                     }
                 }
@@ -294,7 +334,7 @@ pub fn sequential(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         let (built_impl, _, built_where) = built_generics.split_for_impl();
 
         quote! {
-            #[derive(Clone, Debug, dfdx_nn_derives::ResetParams, dfdx_nn_derives::UpdateParams, dfdx_nn_derives::ZeroGrads, dfdx_nn_derives::SaveSafeTensors, dfdx_nn_derives::LoadSafeTensors)]
+            #[derive(Clone, Debug, dfdx_nn_derives::ResetParams, dfdx_nn_derives::VisitTensors, dfdx_nn_derives::SaveSafeTensors, dfdx_nn_derives::LoadSafeTensors, dfdx_nn_derives::SetTraining)]
             pub struct #built_name #built_impl #built_where {
                 #fields
             }
@@ -389,23 +429,37 @@ pub fn sequential(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     };
 
     let impl_module = {
-        let src = match input.data {
+        let (src, src_mut) = match input.data {
             Data::Struct(ref data) => match data.fields {
                 Fields::Named(ref fields) => {
                     let recurse = fields.named.iter().map(|f| {
                         let name = &f.ident;
                         quote_spanned! {f.span()=> self.#name.try_forward(x)? }
                     });
-                    quote! { #(let x = #recurse;)* }
+                    let recurse_mut = fields.named.iter().map(|f| {
+                        let name = &f.ident;
+                        quote_spanned! {f.span()=> self.#name.try_forward_mut(x)? }
+                    });
+                    (
+                        quote! { #(let x = #recurse;)* },
+                        quote! { #(let x = #recurse_mut;)* },
+                    )
                 }
                 Fields::Unnamed(ref fields) => {
                     let recurse = fields.unnamed.iter().enumerate().map(|(i, f)| {
                         let index = Index::from(i);
                         quote_spanned! {f.span()=> self.#index.try_forward(x)? }
                     });
-                    quote! { #(let x = #recurse;)* }
+                    let recurse_mut = fields.unnamed.iter().enumerate().map(|(i, f)| {
+                        let index = Index::from(i);
+                        quote_spanned! {f.span()=> self.#index.try_forward_mut(x)? }
+                    });
+                    (
+                        quote! { #(let x = #recurse;)* },
+                        quote! { #(let x = #recurse_mut;)* },
+                    )
                 }
-                Fields::Unit => quote! { let x = x; },
+                Fields::Unit => (quote! { let x = x; }, quote! { let x = x; }),
             },
             _ => unreachable!(),
         };
@@ -421,6 +475,10 @@ pub fn sequential(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
                     #src
                     Ok(x)
                 }
+                fn try_forward_mut(&mut self, x: Input) -> Result<Self::Output, Self::Error> {
+                    #src_mut
+                    Ok(x)
+                }
             }
         }
     };
@@ -519,11 +577,11 @@ pub fn reset_params(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     })
 }
 
-#[proc_macro_derive(UpdateParams, attributes(param, module))]
-pub fn update_params(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+#[proc_macro_derive(VisitTensors, attributes(param, module, serialize))]
+pub fn visit_tensors(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let mut input = parse_macro_input!(input as DeriveInput);
 
-    let struct_name = input.ident;
+    let name = input.ident;
 
     let mut custom_generics = input.generics.clone();
     if custom_generics
@@ -555,158 +613,134 @@ pub fn update_params(input: proc_macro::TokenStream) -> proc_macro::TokenStream
     }
 
     let where_clause = input.generics.make_where_clause();
-    let updates = match &input.data {
+    let (visits, visits_mut) = match &input.data {
         Data::Struct(ref obj) => match obj.fields {
             Fields::Named(ref fields) => {
-                let updates = fields.named.iter().map(|f| {
+                let mut visits = Vec::new();
+                let mut visits_mut = Vec::new();
+                for f in fields.named.iter() {
                     let name = &f.ident;
                     let ty = &f.ty;
+                    let name_str = name.as_ref().map(|n| n.to_string());
                     if has_attr!(f, "module") {
                         where_clause
                             .predicates
-                            .push(parse_quote!(#ty: dfdx_nn_core::UpdateParams<Elem, Dev>));
-                        quote_spanned!(f.span()=>self.#name.try_update_params(optimizer, gradients, missing_tensors)?;)
+                            .push(parse_quote!(#ty: dfdx_nn_core::VisitTensors<Elem, Dev>));
+                        visits.push(quote_spanned!(f.span()=>self.#name.try_visit_tensors(&format!("{location}{}.", #name_str), visitor)?;));
+                        visits_mut.push(quote_spanned!(f.span()=>self.#name.try_visit_tensors_mut(&format!("{location}{}.", #name_str), visitor)?;));
                     } else if has_attr!(f, "param") {
-                        quote_spanned!(f.span()=>optimizer.update_tensor(&mut self.#name, gradients, missing_tensors)?;)
-                    } else {
-                        Default::default()
+                        visits.push(quote_spanned!(f.span()=>visitor.visit(&format!("{location}{}", #name_str), dfdx_nn_core::TensorKind::Param, &self.#name)?;));
+                        visits_mut.push(quote_spanned!(f.span()=>visitor.visit_mut(&format!("{location}{}", #name_str), dfdx_nn_core::TensorKind::Param, &mut self.#name)?;));
+                    } else if has_attr!(f, "serialize") {
+                        where_clause
+                            .predicates
+                            .push(parse_quote!(#ty: dfdx_nn_core::VisitTensors<Elem, Dev>));
+                        visits.push(quote_spanned!(f.span()=>self.#name.try_visit_tensors(&format!("{location}{}", #name_str), visitor)?;));
+                        visits_mut.push(quote_spanned!(f.span()=>self.#name.try_visit_tensors_mut(&format!("{location}{}", #name_str), visitor)?;));
                     }
-                });
-                quote! { #(#updates)* }
+                }
+                (quote! { #(#visits)* }, quote! { #(#visits_mut)* })
             }
             Fields::Unnamed(ref fields) => {
-                let updates = fields.unnamed.iter().enumerate().map(|(i, f)| {
+                let mut visits = Vec::new();
+                let mut visits_mut = Vec::new();
+                for (i, f) in fields.unnamed.iter().enumerate() {
                     let index = Index::from(i);
                     let ty = &f.ty;
                     if has_attr!(f, "module") {
                         where_clause
                             .predicates
-                            .push(parse_quote!(#ty: dfdx_nn_core::UpdateParams<Elem, Dev>));
-                        quote_spanned!(f.span()=>self.#index.try_update_params(optimizer, gradients, missing_tensors)?;)
+                            .push(parse_quote!(#ty: dfdx_nn_core::VisitTensors<Elem, Dev>));
+                        visits.push(quote_spanned!(f.span()=>self.#index.try_visit_tensors(&format!("{location}{}.", #index), visitor)?;));
+                        visits_mut.push(quote_spanned!(f.span()=>self.#index.try_visit_tensors_mut(&format!("{location}{}.", #index), visitor)?;));
                     } else if has_attr!(f, "param") {
-                        quote_spanned!(f.span()=>optimizer.update_tensor(&mut self.#index, gradients, missing_tensors)?;)
-                    } else {
-                        Default::default()
+                        visits.push(quote_spanned!(f.span()=>visitor.visit(&format!("{location}{}", #index), dfdx_nn_core::TensorKind::Param, &self.#index)?;));
+                        visits_mut.push(quote_spanned!(f.span()=>visitor.visit_mut(&format!("{location}{}", #index), dfdx_nn_core::TensorKind::Param, &mut self.#index)?;));
+                    } else if has_attr!(f, "serialize") {
+                        where_clause
+                            .predicates
+                            .push(parse_quote!(#ty: dfdx_nn_core::VisitTensors<Elem, Dev>));
+                        visits.push(quote_spanned!(f.span()=>self.#index.try_visit_tensors(&format!("{location}{}", #index), visitor)?;));
+                        visits_mut.push(quote_spanned!(f.span()=>self.#index.try_visit_tensors_mut(&format!("{location}{}", #index), visitor)?;));
                     }
-                });
-                quote! { #(#updates)* }
+                }
+                (quote! { #(#visits)* }, quote! { #(#visits_mut)* })
             }
             Fields::Unit => Default::default(),
         },
-        Data::Enum(_) => unimplemented!("UpdateParams not implemented for enums."),
-        Data::Union(_) => unimplemented!("UpdateParams not implemented for unions."),
+        Data::Enum(_) => unimplemented!("VisitTensors not implemented for enums."),
+        Data::Union(_) => unimplemented!("VisitTensors not implemented for unions."),
     };
 
     let (impl_generics, _, _) = custom_generics.split_for_impl();
     let (_, ty_generics, where_clause) = input.generics.split_for_impl();
 
     proc_macro::TokenStream::from(quote! {
-        impl #impl_generics dfdx_nn_core::UpdateParams<Elem, Dev> for #struct_name #ty_generics #where_clause {
-            fn try_update_params<_Model, Optim: dfdx_nn_core::Optimizer<_Model, Elem, Dev>>(
+        impl #impl_generics dfdx_nn_core::VisitTensors<Elem, Dev> for #name #ty_generics #where_clause {
+            fn try_visit_tensors<V: dfdx_nn_core::TensorVisitor<Elem, Dev>>(
+                &self,
+                location: &str,
+                visitor: &mut V,
+            ) -> Result<(), Dev::Err> {
+                #visits
+                Ok(())
+            }
+            fn try_visit_tensors_mut<V: dfdx_nn_core::TensorVisitorMut<Elem, Dev>>(
                 &mut self,
-                optimizer: &mut Optim,
-                gradients: &dfdx::tensor::Gradients<Elem, Dev>,
-                missing_tensors: &mut Vec<dfdx::tensor::UniqueId>,
+                location: &str,
+                visitor: &mut V,
             ) -> Result<(), Dev::Err> {
-                #updates
+                #visits_mut
                 Ok(())
             }
         }
     })
 }
 
+/// Deprecated: `UpdateParams` is implemented for every `VisitTensors` type, so this derives
+/// `VisitTensors` instead. Don't combine it with `#[derive(VisitTensors)]`.
+#[proc_macro_derive(UpdateParams, attributes(param, module, serialize))]
+pub fn update_params(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let span = input_span(input.clone());
+    let mut tokens = visit_tensors(input);
+    tokens.extend(deprecated_derive(
+        span,
+        "UpdateParams",
+        "UpdateParams is implemented for every VisitTensors type, derive VisitTensors instead",
+    ));
+    tokens
+}
+
+/// Removed: `ZeroGrads` is implemented for every `VisitTensors` type. This is an error instead
+/// of a shim like `UpdateParams`, because it's usually derived along with `UpdateParams`, and
+/// both deriving `VisitTensors` would conflict.
 #[proc_macro_derive(ZeroGrads, attributes(param, module))]
 pub fn zero_grads(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let mut input = parse_macro_input!(input as DeriveInput);
-
-    let name = input.ident;
-
-    let mut custom_generics = input.generics.clone();
-    if custom_generics
-        .params
-        .iter()
-        .position(|param| match param {
-            syn::GenericParam::Type(type_param) if type_param.ident == "Elem" => true,
-            _ => false,
-        })
-        .is_none()
-    {
-        custom_generics
-            .params
-            .push(parse_quote!(Elem: dfdx::prelude::Dtype));
-    }
-
-    if custom_generics
-        .params
-        .iter()
-        .position(|param| match param {
-            syn::GenericParam::Type(type_param) if type_param.ident == "Dev" => true,
-            _ => false,
-        })
-        .is_none()
-    {
-        custom_generics
-            .params
-            .push(parse_quote!(Dev: dfdx::prelude::Device<Elem>));
-    }
-
-    let where_clause = input.generics.make_where_clause();
-    let zero_grads = match &input.data {
-        Data::Struct(ref obj) => match obj.fields {
-            Fields::Named(ref fields) => {
-                let zero_grads = fields.named.iter().map(|f| {
-                    let name = &f.ident;
-                    let ty = &f.ty;
-                    if has_attr!(f, "module")
-                    {
-                        where_clause
-                            .predicates
-                            .push(parse_quote!(#ty: dfdx_nn_core::ZeroGrads<Elem, Dev>));
-                        quote_spanned!(f.span()=>self.#name.try_zero_grads(grads)?;)
-                    } else if has_attr!(f, "param")
-                    {
-                        quote_spanned!(f.span()=>self.#name.device().try_fill_with_zeros(grads.get_or_alloc_mut(&self.#name)?)?;)
-                    } else {
-                        Default::default()
-                    }
-                });
-                quote! { #(#zero_grads)* }
-            }
-            Fields::Unnamed(ref fields) => {
-                let zero_grads = fields.unnamed.iter().enumerate().map(|(i, f)| {
-                    let index = Index::from(i);
-                    let ty = &f.ty;
-                    if has_attr!(f, "module")
-                    {
-                        where_clause
-                            .predicates
-                            .push(parse_quote!(#ty: dfdx_nn_core::ZeroGrads<Elem, Dev>));
-                        quote_spanned!(f.span()=>self.#index.try_zero_grads(grads)?;)
-                    } else if has_attr!(f, "param")
-                    {
-                        quote_spanned!(f.span()=>self.#index.device().try_fill_with_zeros(grads.get_or_alloc_mut(&self.#index)?)?)
-                    } else {
-                        Default::default()
-                    }
-                });
-                quote! { #(#zero_grads)* }
-            }
-            Fields::Unit => Default::default(),
-        },
-        Data::Enum(_) => unimplemented!("ZeroGrads not implemented for enums."),
-        Data::Union(_) => unimplemented!("ZeroGrads not implemented for unions."),
-    };
+    let span = input_span(input);
+    proc_macro::TokenStream::from(quote_spanned! {span=>
+        compile_error!(
+            "ZeroGrads is implemented for every VisitTensors type: replace #[derive(ZeroGrads)] \
+             with #[derive(VisitTensors)], or remove it if VisitTensors or UpdateParams is \
+             already derived"
+        );
+    })
+}
 
-    let (impl_generics, _, _) = custom_generics.split_for_impl();
-    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+fn input_span(input: proc_macro::TokenStream) -> proc_macro2::Span {
+    syn::parse::<DeriveInput>(input)
+        .map(|input| input.ident.span())
+        .unwrap_or_else(|_| proc_macro2::Span::call_site())
+}
 
-    proc_macro::TokenStream::from(quote! {
-        impl #impl_generics dfdx_nn_core::ZeroGrads<Elem, Dev> for #name #ty_generics #where_clause {
-            fn try_zero_grads(&self, grads: &mut dfdx::prelude::Gradients<Elem, Dev>) -> Result<(), Dev::Err> {
-                #zero_grads
-                Ok(())
-            }
-        }
+/// Emits a use of a deprecated item at `span`, so deriving `derive` warns with `note`.
+fn deprecated_derive(span: proc_macro2::Span, derive: &str, note: &str) -> proc_macro::TokenStream {
+    let item = syn::Ident::new(&format!("DERIVE_{}", derive.to_uppercase()), span);
+    proc_macro::TokenStream::from(quote_spanned! {span=>
+        const _: () = {
+            #[deprecated(note = #note)]
+            const #item: () = ();
+            #item
+        };
     })
 }
 
@@ -729,7 +763,11 @@ pub fn save_safetensors(input: proc_macro::TokenStream) -> proc_macro::TokenStre
                         where_clause
                             .predicates
                             .push(parse_quote!(#ty: dfdx_nn_core::SaveSafeTensors));
-                        quote_spanned!(f.span()=>self.#name.write_safetensors(&format!("{location}{}", #name_str), tensors);)
+                        if has_attr!(f, "module") {
+                            quote_spanned!(f.span()=>self.#name.write_safetensors(&format!("{location}{}.", #name_str), tensors);)
+                        } else {
+                            quote_spanned!(f.span()=>self.#name.write_safetensors(&format!("{location}{}", #name_str), tensors);)
+                        }
                     } else {
                         Default::default()
                     }
@@ -745,7 +783,11 @@ pub fn save_safetensors(input: proc_macro::TokenStream) -> proc_macro::TokenStre
                         where_clause
                             .predicates
                             .push(parse_quote!(#ty: dfdx_nn_core::SaveSafeTensors));
-                        quote_spanned!(f.span()=>self.#index.write_safetensors(&format!("{location}{}", #index), tensors);)
+                        if has_attr!(f, "module") {
+                            quote_spanned!(f.span()=>self.#index.write_safetensors(&format!("{location}{}.", #index), tensors);)
+                        } else {
+                            quote_spanned!(f.span()=>self.#index.write_safetensors(&format!("{location}{}", #index), tensors);)
+                        }
                     } else {
                         Default::default()
                     }
@@ -791,7 +833,17 @@ pub fn load_safetensors(input: proc_macro::TokenStream) -> proc_macro::TokenStre
                         where_clause
                             .predicates
                             .push(parse_quote!(#ty: dfdx_nn_core::LoadSafeTensors));
-                        quote_spanned!(f.span()=>self.#name.read_safetensors(&format!("{location}{}", #name_str), tensors)?;)
+                        if has_attr!(f, "module") {
+                            // files saved before nested modules were dotted are still loaded
+                            quote_spanned!(f.span()=>match self.#name.read_safetensors(&format!("{location}{}.", #name_str), tensors) {
+                                Err(::safetensors::SafeTensorError::TensorNotFound(_)) => {
+                                    self.#name.read_safetensors(&format!("{location}{}", #name_str), tensors)?;
+                                }
+                                result => result?,
+                            })
+                        } else {
+                            quote_spanned!(f.span()=>self.#name.read_safetensors(&format!("{location}{}", #name_str), tensors)?;)
+                        }
                     } else {
                         Default::default()
                     }
@@ -806,7 +858,17 @@ pub fn load_safetensors(input: proc_macro::TokenStream) -> proc_macro::TokenStre
                         where_clause
                             .predicates
                             .push(parse_quote!(#ty: dfdx_nn_core::LoadSafeTensors));
-                        quote_spanned!(f.span()=>self.#index.read_safetensors(&format!("{location}{}", #index), tensors)?;)
+                        if has_attr!(f, "module") {
+                            // files saved before nested modules were dotted are still loaded
+                            quote_spanned!(f.span()=>match self.#index.read_safetensors(&format!("{location}{}.", #index), tensors) {
+                                Err(::safetensors::SafeTensorError::TensorNotFound(_)) => {
+                                    self.#index.read_safetensors(&format!("{location}{}", #index), tensors)?;
+                                }
+                                result => result?,
+                            })
+                        } else {
+                            quote_spanned!(f.span()=>self.#index.read_safetensors(&format!("{location}{}", #index), tensors)?;)
+                        }
                     } else {
                         Default::default()
                     }
@@ -834,3 +896,63 @@ pub fn load_safetensors(input: proc_macro::TokenStream) -> proc_macro::TokenStre
         }
     })
 }
+
+#[proc_macro_derive(SetTraining, attributes(module, training))]
+pub fn set_training(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let mut input = parse_macro_input!(input as DeriveInput);
+
+    let name = input.ident;
+
+    let where_clause = input.generics.make_where_clause();
+    let sets = match &input.data {
+        Data::Struct(ref obj) => match obj.fields {
+            Fields::Named(ref fields) => {
+                let sets = fields.named.iter().map(|f| {
+                    let name = &f.ident;
+                    let ty = &f.ty;
+                    if has_attr!(f, "module") {
+                        where_clause
+                            .predicates
+                            .push(parse_quote!(#ty: dfdx_nn_core::SetTraining));
+                        quote_spanned!(f.span()=>self.#name.set_training(training);)
+                    } else if has_attr!(f, "training") {
+                        quote_spanned!(f.span()=>self.#name = training;)
+                    } else {
+                        Default::default()
+                    }
+                });
+                quote! { #(#sets)* }
+            }
+            Fields::Unnamed(ref fields) => {
+                let sets = fields.unnamed.iter().enumerate().map(|(i, f)| {
+                    let index = Index::from(i);
+                    let ty = &f.ty;
+                    if has_attr!(f, "module") {
+                        where_clause
+                            .predicates
+                            .push(parse_quote!(#ty: dfdx_nn_core::SetTraining));
+                        quote_spanned!(f.span()=>self.#index.set_training(training);)
+                    } else if has_attr!(f, "training") {
+                        quote_spanned!(f.span()=>self.#index = training;)
+                    } else {
+                        Default::default()
+                    }
+                });
+                quote! { #(#sets)* }
+            }
+            Fields::Unit => Default::default(),
+        },
+        Data::Enum(_) => unimplemented!("SetTraining not implemented for enums."),
+        Data::Union(_) => unimplemented!("SetTraining not implemented for unions."),
+    };
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    proc_macro::TokenStream::from(quote! {
+        impl #impl_generics dfdx_nn_core::SetTraining for #name #ty_generics #where_clause {
+            fn set_training(&mut self, training: bool) {
+                #sets
+            }
+        }
+    })
+}
@@ -22,6 +22,7 @@ fn main() {
         act2: Default::default(),
     };
     let module: MixedMlp<f32, Cpu> = dev.build_module_ext::<f32>(structure);
+    println!("{}", module.summary());
     let x: Tensor<(Const<10>, Const<3>), f32, _> = dev.sample_normal();
     let _: Tensor<(Const<10>, Const<10>), f32, _> = module.forward(x);
 }
@@ -0,0 +1,206 @@
+use crate::*;
+use dfdx::prelude::*;
+
+/// Gaussian error linear unit, `x * Φ(x)`, computed exactly with `erf`.
+#[derive(Default, Debug, Clone, Copy, CustomModule)]
+pub struct GeLU;
+
+impl<S: Shape, E: Dtype, D: Device<E>, T: Tape<E, D>> Module<Tensor<S, E, D, T>> for GeLU {
+    type Output = Tensor<S, E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<S, E, D, T>) -> Result<Self::Output, Self::Error> {
+        x.try_accurate_gelu()
+    }
+}
+
+/// [GeLU] approximated with `tanh`.
+#[derive(Default, Debug, Clone, Copy, CustomModule)]
+pub struct FastGeLU;
+
+impl<S: Shape, E: Dtype, D: Device<E>, T: Tape<E, D>> Module<Tensor<S, E, D, T>> for FastGeLU {
+    type Output = Tensor<S, E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<S, E, D, T>) -> Result<Self::Output, Self::Error> {
+        x.try_fast_gelu()
+    }
+}
+
+/// Sigmoid linear unit, `x * sigmoid(x)`.
+#[derive(Default, Debug, Clone, Copy, CustomModule)]
+pub struct SiLU;
+
+pub type Swish = SiLU;
+
+impl<S: Shape, E: Dtype, D: Device<E>, T: Tape<E, D>> Module<Tensor<S, E, D, T>> for SiLU {
+    type Output = Tensor<S, E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<S, E, D, T>) -> Result<Self::Output, Self::Error> {
+        let gate = x.retaped::<T>().try_sigmoid()?;
+        x.try_mul(gate)
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, CustomModule)]
+pub struct Sigmoid;
+
+impl<S: Shape, E: Dtype, D: Device<E>, T: Tape<E, D>> Module<Tensor<S, E, D, T>> for Sigmoid {
+    type Output = Tensor<S, E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<S, E, D, T>) -> Result<Self::Output, Self::Error> {
+        x.try_sigmoid()
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, CustomModule)]
+pub struct Tanh;
+
+impl<S: Shape, E: Dtype, D: Device<E>, T: Tape<E, D>> Module<Tensor<S, E, D, T>> for Tanh {
+    type Output = Tensor<S, E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<S, E, D, T>) -> Result<Self::Output, Self::Error> {
+        x.try_tanh()
+    }
+}
+
+/// `max(x, 0) + slope * min(x, 0)`.
+#[derive(Debug, Clone, Copy, CustomModule)]
+pub struct LeakyReLU(pub f64);
+
+/// `slope = 0.01`
+impl Default for LeakyReLU {
+    fn default() -> Self {
+        Self(0.01)
+    }
+}
+
+impl<S: Shape, E: Dtype, D: Device<E>, T: Tape<E, D>> Module<Tensor<S, E, D, T>> for LeakyReLU {
+    type Output = Tensor<S, E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<S, E, D, T>) -> Result<Self::Output, Self::Error> {
+        let neg = x
+            .retaped::<T>()
+            .try_negate()?
+            .try_relu()?
+            .try_mul(E::from_f64(self.0).unwrap())?;
+        x.try_relu()?.try_sub(neg)
+    }
+}
+
+/// Exponential linear unit, `x` if `x > 0`, otherwise `alpha * (exp(x) - 1)`.
+#[derive(Debug, Clone, Copy, CustomModule)]
+pub struct ELU(pub f64);
+
+/// `alpha = 1.0`
+impl Default for ELU {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl<S: Shape, E: Dtype, D: Device<E>, T: Tape<E, D>> Module<Tensor<S, E, D, T>> for ELU {
+    type Output = Tensor<S, E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<S, E, D, T>) -> Result<Self::Output, Self::Error> {
+        let neg = x
+            .retaped::<T>()
+            .try_negate()?
+            .try_relu()?
+            .try_negate()?
+            .try_exp()?
+            .try_sub(E::from_f64(1.0).unwrap())?
+            .try_mul(E::from_f64(self.0).unwrap())?;
+        x.try_relu()?.try_add(neg)
+    }
+}
+
+/// `ln(1 + exp(x))`, computed as `max(x, 0) + ln(1 + exp(-|x|))` to not overflow.
+fn try_softplus<S: Shape, E: Dtype, D: Device<E>, T: Tape<E, D>>(
+    x: Tensor<S, E, D, T>,
+) -> Result<Tensor<S, E, D, T>, D::Err> {
+    let t = x
+        .retaped::<T>()
+        .try_abs()?
+        .try_negate()?
+        .try_exp()?
+        .try_add(E::from_f64(1.0).unwrap())?
+        .try_ln()?;
+    x.try_relu()?.try_add(t)
+}
+
+/// `ln(1 + exp(x))`
+#[derive(Default, Debug, Clone, Copy, CustomModule)]
+pub struct Softplus;
+
+impl<S: Shape, E: Dtype, D: Device<E>, T: Tape<E, D>> Module<Tensor<S, E, D, T>> for Softplus {
+    type Output = Tensor<S, E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<S, E, D, T>) -> Result<Self::Output, Self::Error> {
+        try_softplus(x)
+    }
+}
+
+/// `x * tanh(softplus(x))`
+#[derive(Default, Debug, Clone, Copy, CustomModule)]
+pub struct Mish;
+
+impl<S: Shape, E: Dtype, D: Device<E>, T: Tape<E, D>> Module<Tensor<S, E, D, T>> for Mish {
+    type Output = Tensor<S, E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<S, E, D, T>) -> Result<Self::Output, Self::Error> {
+        let gate = try_softplus(x.retaped::<T>())?.try_tanh()?;
+        x.try_mul(gate)
+    }
+}
+
+/// `x * relu6(x + 3) / 6`
+#[derive(Default, Debug, Clone, Copy, CustomModule)]
+pub struct HardSwish;
+
+impl<S: Shape, E: Dtype, D: Device<E>, T: Tape<E, D>> Module<Tensor<S, E, D, T>> for HardSwish {
+    type Output = Tensor<S, E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<S, E, D, T>) -> Result<Self::Output, Self::Error> {
+        let three = E::from_f64(3.0).unwrap();
+        // relu6(x + 3) = relu(x + 3) - relu(x - 3)
+        let upper = x.retaped::<T>().try_sub(three)?.try_relu()?;
+        let gate = x
+            .retaped::<T>()
+            .try_add(three)?
+            .try_relu()?
+            .try_sub(upper)?
+            .try_div(E::from_f64(6.0).unwrap())?;
+        x.try_mul(gate)
+    }
+}
+
+/// Softmax along `Axis<AXIS>`.
+#[derive(Default, Debug, Clone, Copy, CustomModule)]
+pub struct Softmax<const AXIS: isize>;
+
+impl<const AXIS: isize, S: Shape, E: Dtype, D: Device<E>, T: Tape<E, D>> Module<Tensor<S, E, D, T>>
+    for Softmax<AXIS>
+where
+    S: ReduceShape<Axis<AXIS>>,
+{
+    type Output = Tensor<S, E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<S, E, D, T>) -> Result<Self::Output, Self::Error> {
+        x.try_softmax::<Axis<AXIS>>()
+    }
+}
+
+/// Log of the softmax along `Axis<AXIS>`.
+#[derive(Default, Debug, Clone, Copy, CustomModule)]
+pub struct LogSoftmax<const AXIS: isize>;
+
+impl<const AXIS: isize, S: Shape, E: Dtype, D: Device<E>, T: Tape<E, D>> Module<Tensor<S, E, D, T>>
+    for LogSoftmax<AXIS>
+where
+    S: ReduceShape<Axis<AXIS>>,
+{
+    type Output = Tensor<S, E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<S, E, D, T>) -> Result<Self::Output, Self::Error> {
+        x.try_log_softmax::<Axis<AXIS>>()
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use dfdx::{
+    shapes::{Dtype, Shape},
+    tensor::{Gradients, Storage, Tensor, Tensorlike, UniqueId},
+    tensor_ops::{AdamConfig, Device, WeightDecay},
+};
+
+use crate::param_groups::{group_cfg, ParamGroup};
+
+/// The moments & step counts shared by [Adam] and [AdamW].
+#[derive(Debug, Clone)]
+struct AdamState<E: Dtype, D: Storage<E>> {
+    /// Number of updates of each tensor. Counted per tensor so that bias correction
+    /// is right however the update is driven, e.g. through `try_update_params` directly.
+    steps: HashMap<UniqueId, u64>,
+    moment1: Gradients<E, D>,
+    moment2: Gradients<E, D>,
+}
+
+impl<E: Dtype, D: Storage<E>> AdamState<E, D> {
+    fn new() -> Self {
+        Self {
+            steps: HashMap::new(),
+            moment1: Gradients::leaky(),
+            moment2: Gradients::leaky(),
+        }
+    }
+}
+
+impl<E: Dtype, D: Device<E>> AdamState<E, D> {
+    fn try_update<S: Shape>(
+        &mut self,
+        cfg: AdamConfig,
+        param: &mut Tensor<S, E, D>,
+        gradients: &Gradients<E, D>,
+        missing_params: &mut Vec<UniqueId>,
+    ) -> Result<(), D::Err> {
+        let g = gradients.get_ref_checked(param);
+        match g {
+            None => missing_params.push(param.id()),
+            Some(g) => {
+                let t = self.steps.entry(param.id()).or_insert(0);
+                *t += 1;
+                // the bias correction terms `beta^t` have long underflowed
+                // to 0 by the time `t` no longer fits in an i32
+                let t = i32::try_from(*t).unwrap_or(i32::MAX);
+                let m_t = self.moment1.get_or_alloc_mut(param)?;
+                let v_t = self.moment2.get_or_alloc_mut(param)?;
+                cfg.try_update(t, param, m_t, v_t, g)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Adam<M, E: Dtype, D: Storage<E>> {
+    pub cfg: AdamConfig,
+    pub param_groups: Vec<ParamGroup>,
+    state: AdamState<E, D>,
+    module: std::marker::PhantomData<*const M>,
+}
+
+impl<M, E: Dtype, D: Storage<E>> Adam<M, E, D> {
+    pub fn new(_model: &M, cfg: AdamConfig) -> Self {
+        Self {
+            cfg,
+            param_groups: Vec::new(),
+            state: AdamState::new(),
+            module: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<M, E: Dtype, D: Storage<E>> crate::HasLearningRate for Adam<M, E, D> {
+    fn learning_rate(&self) -> f64 {
+        self.cfg.lr
+    }
+    fn learning_rate_mut(&mut self) -> &mut f64 {
+        &mut self.cfg.lr
+    }
+}
+
+impl<M, E: Dtype, D: Device<E>> crate::Optimizer<M, E, D> for Adam<M, E, D> {
+    fn update_tensor<S: Shape>(
+        &mut self,
+        name: &str,
+        param: &mut Tensor<S, E, D>,
+        gradients: &Gradients<E, D>,
+        missing_params: &mut Vec<UniqueId>,
+    ) -> Result<(), D::Err> {
+        let cfg = group_cfg(&self.param_groups, name, self.cfg);
+        self.state.try_update(cfg, param, gradients, missing_params)
+    }
+}
+
+/// Adam with decoupled weight decay, from
+/// [Decoupled Weight Decay Regularization](https://arxiv.org/abs/1711.05101).
+///
+/// The decay is always [WeightDecay::Decoupled] of `weight_decay`, and `cfg.weight_decay` is
+/// ignored. [ParamGroup]s can still change the amount, e.g. `Some(0.0)` for no decay.
+#[derive(Debug, Clone)]
+pub struct AdamW<M, E: Dtype, D: Storage<E>> {
+    pub cfg: AdamConfig,
+    pub weight_decay: f64,
+    pub param_groups: Vec<ParamGroup>,
+    state: AdamState<E, D>,
+    module: std::marker::PhantomData<*const M>,
+}
+
+impl<M, E: Dtype, D: Storage<E>> AdamW<M, E, D> {
+    pub fn new(_model: &M, cfg: AdamConfig, weight_decay: f64) -> Self {
+        Self {
+            cfg,
+            weight_decay,
+            param_groups: Vec::new(),
+            state: AdamState::new(),
+            module: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<M, E: Dtype, D: Storage<E>> crate::HasLearningRate for AdamW<M, E, D> {
+    fn learning_rate(&self) -> f64 {
+        self.cfg.lr
+    }
+    fn learning_rate_mut(&mut self) -> &mut f64 {
+        &mut self.cfg.lr
+    }
+}
+
+impl<M, E: Dtype, D: Device<E>> crate::Optimizer<M, E, D> for AdamW<M, E, D> {
+    fn update_tensor<S: Shape>(
+        &mut self,
+        name: &str,
+        param: &mut Tensor<S, E, D>,
+        gradients: &Gradients<E, D>,
+        missing_params: &mut Vec<UniqueId>,
+    ) -> Result<(), D::Err> {
+        let cfg = AdamConfig {
+            weight_decay: Some(WeightDecay::Decoupled(self.weight_decay)),
+            ..self.cfg
+        };
+        let cfg = group_cfg(&self.param_groups, name, cfg);
+        self.state.try_update(cfg, param, gradients, missing_params)
+    }
+}
@@ -0,0 +1,144 @@
+use crate::*;
+use dfdx::prelude::*;
+
+/// Average pools images into a fixed `(OutH, OutW)` output, for any input size.
+///
+/// Output cell `(i, j)` is the mean of input rows `[i * h / OutH, ceil((i + 1) * h / OutH))`
+/// and the same range of columns.
+#[derive(Debug, Default, Clone, Copy, CustomModule)]
+pub struct AdaptiveAvgPool2D<OutH: Dim, OutW: Dim> {
+    pub out_h: OutH,
+    pub out_w: OutW,
+}
+
+pub type AdaptiveAvgPool2DConst<const OUT_H: usize, const OUT_W: usize> =
+    AdaptiveAvgPool2D<Const<OUT_H>, Const<OUT_W>>;
+
+/// Max pools images into a fixed `(OutH, OutW)` output, for any input size. See
+/// [AdaptiveAvgPool2D] for the cells.
+#[derive(Debug, Default, Clone, Copy, CustomModule)]
+pub struct AdaptiveMaxPool2D<OutH: Dim, OutW: Dim> {
+    pub out_h: OutH,
+    pub out_w: OutW,
+}
+
+pub type AdaptiveMaxPool2DConst<const OUT_H: usize, const OUT_W: usize> =
+    AdaptiveMaxPool2D<Const<OUT_H>, Const<OUT_W>>;
+
+/// The input indices pooled into each of the `output` cells.
+fn bins(input: usize, output: usize) -> impl Iterator<Item = std::ops::Range<usize>> {
+    (0..output).map(move |i| (i * input) / output..((i + 1) * input + output - 1) / output)
+}
+
+fn try_adaptive_pool2d<C: Dim, H: Dim, W: Dim, OH: Dim, OW: Dim, E, D, T>(
+    x: Tensor<(C, H, W), E, D, T>,
+    kind: Pool2DKind,
+    (oh, ow): (OH, OW),
+) -> Result<Tensor<(C, OH, OW), E, D, T>, D::Err>
+where
+    E: Dtype,
+    D: Device<E>,
+    T: Tape<E, D>,
+{
+    let (c, h, w) = *x.shape();
+    let (x, mut tape) = x.split_tape();
+    let mut cells = Vec::with_capacity(oh.size() * ow.size());
+    for rows in bins(h.size(), oh.size()) {
+        for cols in bins(w.size(), ow.size()) {
+            // only the first cell carries the existing tape, the rest are merged into it
+            let cell = x.clone().put_tape(std::mem::take(&mut tape)).try_slice((
+                ..,
+                rows.clone(),
+                cols,
+            ))?;
+            cells.push(match kind {
+                Pool2DKind::Avg => cell.try_mean::<(C,), _>()?,
+                Pool2DKind::Min => cell.try_min::<(C,), _>()?,
+                Pool2DKind::Max => cell.try_max::<(C,), _>()?,
+            });
+        }
+    }
+    cells
+        .try_stack()?
+        .try_reshape_like(&(oh, ow, c))?
+        .try_permute::<_, Axes3<2, 0, 1>>()
+}
+
+fn try_adaptive_pool2d_batched<B: Dim, C: Dim, H: Dim, W: Dim, OH: Dim, OW: Dim, E, D, T>(
+    x: Tensor<(B, C, H, W), E, D, T>,
+    kind: Pool2DKind,
+    (oh, ow): (OH, OW),
+) -> Result<Tensor<(B, C, OH, OW), E, D, T>, D::Err>
+where
+    E: Dtype,
+    D: Device<E>,
+    T: Tape<E, D>,
+{
+    let (b, c, h, w) = *x.shape();
+    let (x, mut tape) = x.split_tape();
+    let mut cells = Vec::with_capacity(oh.size() * ow.size());
+    for rows in bins(h.size(), oh.size()) {
+        for cols in bins(w.size(), ow.size()) {
+            // only the first cell carries the existing tape, the rest are merged into it
+            let cell = x.clone().put_tape(std::mem::take(&mut tape)).try_slice((
+                ..,
+                ..,
+                rows.clone(),
+                cols,
+            ))?;
+            cells.push(match kind {
+                Pool2DKind::Avg => cell.try_mean::<(B, C), _>()?,
+                Pool2DKind::Min => cell.try_min::<(B, C), _>()?,
+                Pool2DKind::Max => cell.try_max::<(B, C), _>()?,
+            });
+        }
+    }
+    cells
+        .try_stack()?
+        .try_reshape_like(&(oh, ow, b, c))?
+        .try_permute::<_, Axes4<2, 3, 0, 1>>()
+}
+
+impl<OH: Dim, OW: Dim, C: Dim, H: Dim, W: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<Tensor<(C, H, W), E, D, T>> for AdaptiveAvgPool2D<OH, OW>
+{
+    type Output = Tensor<(C, OH, OW), E, D, T>;
+    type Error = D::Err;
+
+    fn try_forward(&self, x: Tensor<(C, H, W), E, D, T>) -> Result<Self::Output, D::Err> {
+        try_adaptive_pool2d(x, Pool2DKind::Avg, (self.out_h, self.out_w))
+    }
+}
+
+impl<OH: Dim, OW: Dim, B: Dim, C: Dim, H: Dim, W: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<Tensor<(B, C, H, W), E, D, T>> for AdaptiveAvgPool2D<OH, OW>
+{
+    type Output = Tensor<(B, C, OH, OW), E, D, T>;
+    type Error = D::Err;
+
+    fn try_forward(&self, x: Tensor<(B, C, H, W), E, D, T>) -> Result<Self::Output, D::Err> {
+        try_adaptive_pool2d_batched(x, Pool2DKind::Avg, (self.out_h, self.out_w))
+    }
+}
+
+impl<OH: Dim, OW: Dim, C: Dim, H: Dim, W: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<Tensor<(C, H, W), E, D, T>> for AdaptiveMaxPool2D<OH, OW>
+{
+    type Output = Tensor<(C, OH, OW), E, D, T>;
+    type Error = D::Err;
+
+    fn try_forward(&self, x: Tensor<(C, H, W), E, D, T>) -> Result<Self::Output, D::Err> {
+        try_adaptive_pool2d(x, Pool2DKind::Max, (self.out_h, self.out_w))
+    }
+}
+
+impl<OH: Dim, OW: Dim, B: Dim, C: Dim, H: Dim, W: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<Tensor<(B, C, H, W), E, D, T>> for AdaptiveMaxPool2D<OH, OW>
+{
+    type Output = Tensor<(B, C, OH, OW), E, D, T>;
+    type Error = D::Err;
+
+    fn try_forward(&self, x: Tensor<(B, C, H, W), E, D, T>) -> Result<Self::Output, D::Err> {
+        try_adaptive_pool2d_batched(x, Pool2DKind::Max, (self.out_h, self.out_w))
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{
+    batch_norm2d::{infer_fwd, train_fwd},
+    LoadSafeTensors, SaveSafeTensors, SetTraining, VisitTensors,
+};
+use dfdx::prelude::*;
+
+#[derive(Default, Clone, Copy, Debug)]
+#[repr(transparent)]
+pub struct BatchNorm1DConfig<C: Dim>(pub C);
+
+pub type BatchNorm1DConstConfig<const C: usize> = BatchNorm1DConfig<Const<C>>;
+
+impl<C: Dim, E: Dtype, D: Device<E>> crate::BuildOnDevice<E, D> for BatchNorm1DConfig<C> {
+    type Built = BatchNorm1D<C, E, D>;
+    fn try_build_on_device(&self, device: &D) -> Result<Self::Built, D::Err> {
+        Ok(BatchNorm1D {
+            scale: device.try_ones_like(&(self.0,))?,
+            bias: device.try_zeros_like(&(self.0,))?,
+            running_mean: device.try_zeros_like(&(self.0,))?,
+            running_var: device.try_ones_like(&(self.0,))?,
+            epsilon: 1e-5,
+            momentum: 0.1,
+            training: true,
+        })
+    }
+}
+
+/// Batch normalization over the features of `(Batch, C)` or the channels of `(Batch, C, L)`.
+///
+/// Same train/eval behavior and safetensors layout as [crate::BatchNorm2D].
+#[derive(Clone, Debug, VisitTensors, SaveSafeTensors, LoadSafeTensors, SetTraining)]
+pub struct BatchNorm1D<C: Dim, Elem: Dtype, Dev: Device<Elem>> {
+    #[param]
+    #[serialize]
+    pub scale: Tensor<(C,), Elem, Dev>,
+    #[param]
+    #[serialize]
+    pub bias: Tensor<(C,), Elem, Dev>,
+    #[serialize]
+    pub running_mean: Tensor<(C,), Elem, Dev>,
+    #[serialize]
+    pub running_var: Tensor<(C,), Elem, Dev>,
+    #[serialize]
+    pub epsilon: f64,
+    #[serialize]
+    pub momentum: f64,
+    #[training]
+    pub training: bool,
+}
+
+impl<C: Dim, E: Dtype, D: Device<E>> crate::ResetParams<E, D> for BatchNorm1D<C, E, D> {
+    fn try_reset_params(&mut self) -> Result<(), D::Err> {
+        self.scale.try_fill_with_ones()?;
+        self.bias.try_fill_with_zeros()?;
+        self.running_mean.try_fill_with_zeros()?;
+        self.running_var.try_fill_with_ones()
+    }
+}
+
+impl<Batch: Dim, C: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    crate::Module<Tensor<(Batch, C), E, D, T>> for BatchNorm1D<C, E, D>
+{
+    type Output = Tensor<(Batch, C), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<(Batch, C), E, D, T>) -> Result<Self::Output, Self::Error> {
+        self.infer_fwd(x)
+    }
+    fn try_forward_mut(
+        &mut self,
+        x: Tensor<(Batch, C), E, D, T>,
+    ) -> Result<Self::Output, Self::Error> {
+        if self.training {
+            self.train_fwd(x)
+        } else {
+            self.infer_fwd(x)
+        }
+    }
+}
+
+impl<Batch: Dim, C: Dim, L: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    crate::Module<Tensor<(Batch, C, L), E, D, T>> for BatchNorm1D<C, E, D>
+{
+    type Output = Tensor<(Batch, C, L), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<(Batch, C, L), E, D, T>) -> Result<Self::Output, Self::Error> {
+        self.infer_fwd(x)
+    }
+    fn try_forward_mut(
+        &mut self,
+        x: Tensor<(Batch, C, L), E, D, T>,
+    ) -> Result<Self::Output, Self::Error> {
+        if self.training {
+            self.train_fwd(x)
+        } else {
+            self.infer_fwd(x)
+        }
+    }
+}
+
+impl<C: Dim, E: Dtype, D: Device<E>> BatchNorm1D<C, E, D> {
+    /// generic batchnorm forward for training
+    fn train_fwd<S: Shape, T: Tape<E, D>, Ax: Axes>(
+        &mut self,
+        x: Tensor<S, E, D, T>,
+    ) -> Result<Tensor<S, E, D, T>, D::Err>
+    where
+        S: HasAxes<Ax> + ReduceShapeTo<(C,), Ax>,
+    {
+        train_fwd(
+            x,
+            &self.scale,
+            &self.bias,
+            &mut self.running_mean,
+            &mut self.running_var,
+            self.epsilon,
+            self.momentum,
+        )
+    }
+
+    /// generic batchnorm forward for inference
+    pub fn infer_fwd<S: Shape, T: Tape<E, D>, Ax: Axes>(
+        &self,
+        x: Tensor<S, E, D, T>,
+    ) -> Result<Tensor<S, E, D, T>, D::Err>
+    where
+        (C,): BroadcastShapeTo<S, Ax>,
+    {
+        infer_fwd(
+            x,
+            &self.scale,
+            &self.bias,
+            &self.running_mean,
+            &self.running_var,
+            self.epsilon,
+        )
+    }
+}
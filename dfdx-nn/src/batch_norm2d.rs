@@ -1,4 +1,4 @@
-use crate::{LoadSafeTensors, SaveSafeTensors, UpdateParams, ZeroGrads};
+use crate::{LoadSafeTensors, SaveSafeTensors, SetTraining, VisitTensors};
 use dfdx::prelude::*;
 
 #[derive(Default, Clone, Copy, Debug)]
@@ -17,11 +17,18 @@ impl<C: Dim, E: Dtype, D: Device<E>> crate::BuildOnDevice<E, D> for BatchNorm2DC
             running_var: device.try_ones_like(&(self.0,))?,
             epsilon: 1e-5,
             momentum: 0.1,
+            training: true,
         })
     }
 }
 
-#[derive(Clone, Debug, UpdateParams, ZeroGrads, SaveSafeTensors, LoadSafeTensors)]
+/// Batch normalization over the channels of images.
+///
+/// [crate::Module::try_forward] always normalizes with the running statistics, with or without
+/// a tape. In training mode (see [crate::SetTraining]), [crate::Module::try_forward_mut]
+/// normalizes with the statistics of the batch and updates the running statistics. In eval
+/// mode it uses the running statistics too.
+#[derive(Clone, Debug, VisitTensors, SaveSafeTensors, LoadSafeTensors, SetTraining)]
 pub struct BatchNorm2D<C: Dim, Elem: Dtype, Dev: Device<Elem>> {
     #[param]
     #[serialize]
@@ -37,6 +44,8 @@ pub struct BatchNorm2D<C: Dim, Elem: Dtype, Dev: Device<Elem>> {
     pub epsilon: f64,
     #[serialize]
     pub momentum: f64,
+    #[training]
+    pub training: bool,
 }
 
 impl<C: Dim, E: Dtype, D: Device<E>> crate::ResetParams<E, D> for BatchNorm2D<C, E, D> {
@@ -54,15 +63,17 @@ impl<C: Dim, H: Dim, W: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
     type Output = Tensor<(C, H, W), E, D, T>;
     type Error = D::Err;
     fn try_forward(&self, x: Tensor<(C, H, W), E, D, T>) -> Result<Self::Output, Self::Error> {
-        assert!(!T::OWNS_TAPE);
         self.infer_fwd(x)
     }
     fn try_forward_mut(
         &mut self,
         x: Tensor<(C, H, W), E, D, T>,
     ) -> Result<Self::Output, Self::Error> {
-        assert!(T::OWNS_TAPE);
-        self.train_fwd(x)
+        if self.training {
+            self.train_fwd(x)
+        } else {
+            self.infer_fwd(x)
+        }
     }
 }
 
@@ -75,15 +86,17 @@ impl<Batch: Dim, C: Dim, H: Dim, W: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
         &self,
         x: Tensor<(Batch, C, H, W), E, D, T>,
     ) -> Result<Self::Output, Self::Error> {
-        assert!(!T::OWNS_TAPE);
         self.infer_fwd(x)
     }
     fn try_forward_mut(
         &mut self,
         x: Tensor<(Batch, C, H, W), E, D, T>,
     ) -> Result<Self::Output, Self::Error> {
-        assert!(T::OWNS_TAPE);
-        self.train_fwd(x)
+        if self.training {
+            self.train_fwd(x)
+        } else {
+            self.infer_fwd(x)
+        }
     }
 }
 
@@ -96,45 +109,15 @@ impl<C: Dim, E: Dtype, D: Device<E>> BatchNorm2D<C, E, D> {
     where
         S: HasAxes<Ax> + ReduceShapeTo<(C,), Ax>,
     {
-        let n = <S as HasAxes<Ax>>::size(x.shape()) as f64;
-        let shape = *x.shape();
-
-        // compute statistics for updating running stats later - on tape
-        let mean_chan = x.retaped::<T>().try_mean::<(C,), _>()?;
-
-        // update statistics since we are training - off tape
-        self.running_mean
-            .try_axpy(1.0 - self.momentum, &mean_chan, self.momentum)?;
-
-        let centered = x.try_sub(mean_chan.try_broadcast_like(&shape)?)?;
-
-        let var_chan = centered
-            .retaped::<T>()
-            .try_square()?
-            .try_mean::<(C,), _>()?;
-
-        // NOTE: uses unbiased variance in running estimate
-        self.running_var.try_axpy(
-            1.0 - self.momentum,
-            &var_chan,
-            self.momentum * n / (n - 1.0),
-        )?;
-
-        // statistics for normalizing - on tape
-        let std = var_chan
-            .try_add(E::from_f64(self.epsilon).unwrap())?
-            .try_sqrt()?;
-
-        // record broadcast of scale & bias - on tape
-        let scale = self
-            .scale
-            .retaped::<T>()
-            .try_div(std)?
-            .try_broadcast_like(&shape)?;
-        let bias = self.bias.retaped::<T>().try_broadcast_like(&shape)?;
-
-        // normalize & affine - on tape
-        centered.try_mul(scale)?.try_add(bias)
+        train_fwd(
+            x,
+            &self.scale,
+            &self.bias,
+            &mut self.running_mean,
+            &mut self.running_var,
+            self.epsilon,
+            self.momentum,
+        )
     }
 
     /// generic batchnorm forward for inference
@@ -145,24 +128,89 @@ impl<C: Dim, E: Dtype, D: Device<E>> BatchNorm2D<C, E, D> {
     where
         (C,): BroadcastShapeTo<S, Ax>,
     {
-        let shape = *x.shape();
-
-        // statistics for normalizing
-        let std = self
-            .running_var
-            .clone()
-            .try_add(E::from_f64(self.epsilon).unwrap())?
-            .try_sqrt()?;
-
-        let scale = self
-            .scale
-            .clone()
-            .try_div(std)?
-            .try_broadcast_like(&shape)?;
-
-        // normalize & affine
-        let x = x.try_sub(self.running_mean.clone().try_broadcast_like(&shape)?)?;
-        let x = x.try_mul(scale)?;
-        x.try_add(self.bias.clone().try_broadcast_like(&shape)?)
+        infer_fwd(
+            x,
+            &self.scale,
+            &self.bias,
+            &self.running_mean,
+            &self.running_var,
+            self.epsilon,
+        )
     }
 }
+
+/// generic batchnorm forward for training, normalizes over every axis of `S` except `C`
+pub(crate) fn train_fwd<C: Dim, S: Shape, E: Dtype, D: Device<E>, T: Tape<E, D>, Ax: Axes>(
+    x: Tensor<S, E, D, T>,
+    scale: &Tensor<(C,), E, D>,
+    bias: &Tensor<(C,), E, D>,
+    running_mean: &mut Tensor<(C,), E, D>,
+    running_var: &mut Tensor<(C,), E, D>,
+    epsilon: f64,
+    momentum: f64,
+) -> Result<Tensor<S, E, D, T>, D::Err>
+where
+    S: HasAxes<Ax> + ReduceShapeTo<(C,), Ax>,
+{
+    let n = <S as HasAxes<Ax>>::size(x.shape()) as f64;
+    let shape = *x.shape();
+
+    // compute statistics for updating running stats later - on tape
+    let mean_chan = x.retaped::<T>().try_mean::<(C,), _>()?;
+
+    // update statistics since we are training - off tape
+    running_mean.try_axpy(1.0 - momentum, &mean_chan, momentum)?;
+
+    let centered = x.try_sub(mean_chan.try_broadcast_like(&shape)?)?;
+
+    let var_chan = centered
+        .retaped::<T>()
+        .try_square()?
+        .try_mean::<(C,), _>()?;
+
+    // NOTE: uses unbiased variance in running estimate
+    running_var.try_axpy(1.0 - momentum, &var_chan, momentum * n / (n - 1.0))?;
+
+    // statistics for normalizing - on tape
+    let std = var_chan
+        .try_add(E::from_f64(epsilon).unwrap())?
+        .try_sqrt()?;
+
+    // record broadcast of scale & bias - on tape
+    let scale = scale
+        .retaped::<T>()
+        .try_div(std)?
+        .try_broadcast_like(&shape)?;
+    let bias = bias.retaped::<T>().try_broadcast_like(&shape)?;
+
+    // normalize & affine - on tape
+    centered.try_mul(scale)?.try_add(bias)
+}
+
+/// generic batchnorm forward for inference
+pub(crate) fn infer_fwd<C: Dim, S: Shape, E: Dtype, D: Device<E>, T: Tape<E, D>, Ax: Axes>(
+    x: Tensor<S, E, D, T>,
+    scale: &Tensor<(C,), E, D>,
+    bias: &Tensor<(C,), E, D>,
+    running_mean: &Tensor<(C,), E, D>,
+    running_var: &Tensor<(C,), E, D>,
+    epsilon: f64,
+) -> Result<Tensor<S, E, D, T>, D::Err>
+where
+    (C,): BroadcastShapeTo<S, Ax>,
+{
+    let shape = *x.shape();
+
+    // statistics for normalizing
+    let std = running_var
+        .clone()
+        .try_add(E::from_f64(epsilon).unwrap())?
+        .try_sqrt()?;
+
+    let scale = scale.clone().try_div(std)?.try_broadcast_like(&shape)?;
+
+    // normalize & affine
+    let x = x.try_sub(running_mean.clone().try_broadcast_like(&shape)?)?;
+    let x = x.try_mul(scale)?;
+    x.try_add(bias.clone().try_broadcast_like(&shape)?)
+}
@@ -21,7 +21,7 @@ impl<I: Dim, E: Dtype, D: Device<E>> BuildOnDevice<E, D> for Bias2DConfig<I> {
     }
 }
 
-#[derive(Clone, Debug, UpdateParams, ZeroGrads, SaveSafeTensors, LoadSafeTensors)]
+#[derive(Clone, Debug, VisitTensors, SaveSafeTensors, LoadSafeTensors, SetTraining)]
 pub struct Bias2D<I: Dim, Elem: Dtype, Dev: Device<Elem>> {
     #[param]
     #[serialize]
@@ -0,0 +1,301 @@
+use dfdx::prelude::*;
+
+use crate::*;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ConvTrans2DConfig<
+    InChan: Dim,
+    OutChan: Dim,
+    KernelSize: Dim,
+    Stride: Dim = Const<1>,
+    Padding: Dim = Const<0>,
+    OutputPadding: Dim = Const<0>,
+    Dilation: Dim = Const<1>,
+    Groups: Dim = Const<1>,
+> {
+    pub in_chan: InChan,
+    pub out_chan: OutChan,
+    pub kernel_size: KernelSize,
+    pub stride: Stride,
+    pub padding: Padding,
+    pub output_padding: OutputPadding,
+    pub dilation: Dilation,
+    pub groups: Groups,
+}
+
+pub type ConvTrans2DConstConfig<
+    const IN_CHAN: usize,
+    const OUT_CHAN: usize,
+    const KERNEL_SIZE: usize,
+    const STRIDE: usize = 1,
+    const PADDING: usize = 0,
+    const OUTPUT_PADDING: usize = 0,
+    const DILATION: usize = 1,
+    const GROUPS: usize = 1,
+> = ConvTrans2DConfig<
+    Const<IN_CHAN>,
+    Const<OUT_CHAN>,
+    Const<KERNEL_SIZE>,
+    Const<STRIDE>,
+    Const<PADDING>,
+    Const<OUTPUT_PADDING>,
+    Const<DILATION>,
+    Const<GROUPS>,
+>;
+
+impl<I: Dim, O: Dim, K: Dim, S: Dim, P: Dim, OP: Dim, L: Dim, G: Dim, E: Dtype, D: Device<E>>
+    crate::BuildOnDevice<E, D> for ConvTrans2DConfig<I, O, K, S, P, OP, L, G>
+where
+    O: std::ops::Div<G>,
+    <O as std::ops::Div<G>>::Output: Dim,
+{
+    type Built = ConvTrans2D<I, O, K, S, P, OP, L, G, E, D>;
+    fn try_build_on_device(&self, device: &D) -> Result<Self::Built, <D>::Err> {
+        assert_eq!(self.in_chan.size() % self.groups.size(), 0);
+        assert_eq!(self.out_chan.size() % self.groups.size(), 0);
+        assert!(
+            self.output_padding.size() < self.stride.size().max(self.dilation.size()),
+            "output padding must be smaller than either stride or dilation"
+        );
+        let o_over_g = self.out_chan / self.groups;
+        let weight =
+            device.try_zeros_like(&(self.in_chan, o_over_g, self.kernel_size, self.kernel_size))?;
+        Ok(ConvTrans2D {
+            weight,
+            stride: self.stride,
+            padding: self.padding,
+            output_padding: self.output_padding,
+            dilation: self.dilation,
+            groups: self.groups,
+        })
+    }
+}
+
+/// Transposed 2d convolution, the gradient of [Conv2D] w.r.t. its input.
+///
+/// `weight` has the same layout as PyTorch's `ConvTranspose2d`:
+/// `(in_chan, out_chan / groups, kernel_size, kernel_size)`.
+///
+/// `output_padding` adds that many rows & columns to the bottom & right of the output, to resolve
+/// the ambiguous output size when `stride > 1`. Like PyTorch, it must be smaller than either
+/// `stride` or `dilation`. The output height is
+/// `(h - 1) * stride - 2 * padding + dilation * (kernel_size - 1) + output_padding + 1`, and is
+/// static when `h` and the config are.
+#[derive(Debug, Clone, VisitTensors, SaveSafeTensors, LoadSafeTensors, SetTraining)]
+pub struct ConvTrans2D<
+    InChan,
+    OutChan,
+    KernelSize,
+    Stride,
+    Padding,
+    OutputPadding,
+    Dilation,
+    Groups,
+    Elem,
+    Dev,
+> where
+    OutChan: std::ops::Div<Groups>,
+    <OutChan as std::ops::Div<Groups>>::Output: Dim,
+    InChan: Dim,
+    OutChan: Dim,
+    KernelSize: Dim,
+    Stride: Dim,
+    Padding: Dim,
+    OutputPadding: Dim,
+    Dilation: Dim,
+    Groups: Dim,
+    Elem: Dtype,
+    Dev: Device<Elem>,
+{
+    #[param]
+    #[serialize]
+    pub weight: Tensor<
+        (
+            InChan,
+            <OutChan as std::ops::Div<Groups>>::Output,
+            KernelSize,
+            KernelSize,
+        ),
+        Elem,
+        Dev,
+    >,
+    pub stride: Stride,
+    pub padding: Padding,
+    pub output_padding: OutputPadding,
+    pub dilation: Dilation,
+    pub groups: Groups,
+}
+
+impl<I: Dim, O: Dim, K: Dim, S: Dim, P: Dim, OP: Dim, L: Dim, G: Dim, E, D> crate::ResetParams<E, D>
+    for ConvTrans2D<I, O, K, S, P, OP, L, G, E, D>
+where
+    O: std::ops::Div<G>,
+    <O as std::ops::Div<G>>::Output: Dim,
+    E: Dtype + num_traits::Float + rand_distr::uniform::SampleUniform,
+    D: Device<E>,
+{
+    fn try_reset_params(&mut self) -> Result<(), D::Err> {
+        let (_, o_over_g, k, _) = self.weight.shape();
+        let scale = E::from_f64(1.0 / (k.size() * k.size() * o_over_g.size()) as f64).unwrap();
+        let b = scale.sqrt();
+        self.weight
+            .try_fill_with_distr(rand_distr::Uniform::new(-b, b))
+    }
+}
+
+impl<I: Dim, O: Dim, K: Dim, S: Dim, P: Dim, OP: Dim, L: Dim, G: Dim, E, D>
+    ConvTrans2D<I, O, K, S, P, OP, L, G, E, D>
+where
+    O: std::ops::Div<G>,
+    <O as std::ops::Div<G>>::Output: Dim,
+    E: Dtype,
+    D: Device<E>,
+{
+    /// The rows & columns of the unpadded output, extended by `output_padding` zeros at the end,
+    /// that are kept.
+    fn output_range(&self, full: usize) -> std::ops::Range<usize> {
+        let p = self.padding.size();
+        p..full + self.output_padding.size() - p
+    }
+}
+
+impl<I: Dim, O: Dim, K: Dim, S: Dim, P: Dim, OP: Dim, L: Dim, G: Dim, E, D, H, W, T, Hp, Wp>
+    crate::Module<Tensor<(I, H, W), E, D, T>> for ConvTrans2D<I, O, K, S, P, OP, L, G, E, D>
+where
+    O: std::ops::Div<G>,
+    <O as std::ops::Div<G>>::Output: Dim,
+    E: Dtype,
+    D: Device<E>,
+    H: Dim,
+    W: Dim,
+    T: Tape<E, D>,
+    Hp: Dim + std::ops::Add<OP>,
+    Wp: Dim + std::ops::Add<OP>,
+    <Hp as std::ops::Add<OP>>::Output: Dim,
+    <Wp as std::ops::Add<OP>>::Output: Dim,
+    (
+        Tensor<(I, H, W), E, D, T>,
+        Tensor<(I, <O as std::ops::Div<G>>::Output, K, K), E, D>,
+    ): TryConvTrans2D<S, P, L, G, Convolved = Tensor<(O, Hp, Wp), E, D, T>>,
+    (
+        Tensor<(I, usize, usize), E, D, T>,
+        Tensor<(I, <O as std::ops::Div<G>>::Output, K, K), E, D>,
+    ): TryConvTrans2D<
+        S,
+        Const<0>,
+        L,
+        G,
+        Convolved = Tensor<(O, usize, usize), E, D, T>,
+        Error = D::Err,
+    >,
+{
+    type Output = Tensor<
+        (
+            O,
+            <Hp as std::ops::Add<OP>>::Output,
+            <Wp as std::ops::Add<OP>>::Output,
+        ),
+        E,
+        D,
+        T,
+    >;
+    type Error = D::Err;
+
+    fn try_forward(&self, x: Tensor<(I, H, W), E, D, T>) -> Result<Self::Output, Self::Error> {
+        let (i, h, w) = *x.shape();
+        let x = x.try_reshape_like(&(i, h.size(), w.size()))?;
+        let y = (x, self.weight.clone()).try_convtrans2d(
+            self.stride,
+            Const::<0>,
+            self.dilation,
+            self.groups,
+        )?;
+
+        // output padding is zeros at the bottom & right of the unpadded output
+        let (o, h, w) = *y.shape();
+        let op = self.output_padding.size();
+        let dev = y.device().clone();
+        let y = (y, dev.try_zeros_like(&(o, op, w))?).try_concat_along(Axis::<1>)?;
+        let y = (y, dev.try_zeros_like(&(o, h + op, op))?).try_concat_along(Axis::<2>)?;
+
+        let y = y.try_slice((.., self.output_range(h), self.output_range(w)))?;
+        let (_, h, w) = *y.shape();
+        y.try_reshape_like(&(
+            o,
+            <Hp as std::ops::Add<OP>>::Output::from_size(h).unwrap(),
+            <Wp as std::ops::Add<OP>>::Output::from_size(w).unwrap(),
+        ))
+    }
+}
+
+impl<I: Dim, O: Dim, K: Dim, S: Dim, P: Dim, OP: Dim, L: Dim, G: Dim, E, D, B, H, W, T, Hp, Wp>
+    crate::Module<Tensor<(B, I, H, W), E, D, T>> for ConvTrans2D<I, O, K, S, P, OP, L, G, E, D>
+where
+    O: std::ops::Div<G>,
+    <O as std::ops::Div<G>>::Output: Dim,
+    E: Dtype,
+    D: Device<E>,
+    B: Dim,
+    H: Dim,
+    W: Dim,
+    T: Tape<E, D>,
+    Hp: Dim + std::ops::Add<OP>,
+    Wp: Dim + std::ops::Add<OP>,
+    <Hp as std::ops::Add<OP>>::Output: Dim,
+    <Wp as std::ops::Add<OP>>::Output: Dim,
+    (
+        Tensor<(B, I, H, W), E, D, T>,
+        Tensor<(I, <O as std::ops::Div<G>>::Output, K, K), E, D>,
+    ): TryConvTrans2D<S, P, L, G, Convolved = Tensor<(B, O, Hp, Wp), E, D, T>>,
+    (
+        Tensor<(B, I, usize, usize), E, D, T>,
+        Tensor<(I, <O as std::ops::Div<G>>::Output, K, K), E, D>,
+    ): TryConvTrans2D<
+        S,
+        Const<0>,
+        L,
+        G,
+        Convolved = Tensor<(B, O, usize, usize), E, D, T>,
+        Error = D::Err,
+    >,
+{
+    type Output = Tensor<
+        (
+            B,
+            O,
+            <Hp as std::ops::Add<OP>>::Output,
+            <Wp as std::ops::Add<OP>>::Output,
+        ),
+        E,
+        D,
+        T,
+    >;
+    type Error = D::Err;
+
+    fn try_forward(&self, x: Tensor<(B, I, H, W), E, D, T>) -> Result<Self::Output, Self::Error> {
+        let (b, i, h, w) = *x.shape();
+        let x = x.try_reshape_like(&(b, i, h.size(), w.size()))?;
+        let y = (x, self.weight.clone()).try_convtrans2d(
+            self.stride,
+            Const::<0>,
+            self.dilation,
+            self.groups,
+        )?;
+
+        // output padding is zeros at the bottom & right of the unpadded output
+        let (b, o, h, w) = *y.shape();
+        let op = self.output_padding.size();
+        let dev = y.device().clone();
+        let y = (y, dev.try_zeros_like(&(b, o, op, w))?).try_concat_along(Axis::<2>)?;
+        let y = (y, dev.try_zeros_like(&(b, o, h + op, op))?).try_concat_along(Axis::<3>)?;
+
+        let y = y.try_slice((.., .., self.output_range(h), self.output_range(w)))?;
+        let (_, _, h, w) = *y.shape();
+        y.try_reshape_like(&(
+            b,
+            o,
+            <Hp as std::ops::Add<OP>>::Output::from_size(h).unwrap(),
+            <Wp as std::ops::Add<OP>>::Output::from_size(w).unwrap(),
+        ))
+    }
+}
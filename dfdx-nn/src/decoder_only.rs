@@ -0,0 +1,223 @@
+use crate::*;
+use dfdx::prelude::*;
+
+/// A GPT-style decoder only language model: token & position embeddings, a stack of causal
+/// self attention blocks (with no cross attention), a final norm and an LM head.
+///
+/// Takes `(B, S)` token ids and returns `(B, S, Vocab)` logits.
+///
+/// [DecoderOnlyConfig::new] uses [NormPlacement::Pre] blocks and learned position embeddings
+/// like GPT-2. The head can share its weight with the token embedding, see
+/// [DecoderOnlyConfig::with_tied_embeddings].
+#[derive(Clone, Debug, CustomModule)]
+#[built(DecoderOnly)]
+pub struct DecoderOnlyConfig<
+    Vocab: Dim,
+    Model: Dim,
+    NumHeads: Dim,
+    F: Dim,
+    Norm: std::fmt::Debug = LayerNorm1DConfig<Model>,
+    FF: std::fmt::Debug = FeedForwardConfig<Model, F>,
+    Pos: std::fmt::Debug = LearnedPositionalEmbeddingConfig<usize, Model>,
+> {
+    #[module]
+    pub embedding: EmbeddingConfig<Vocab, Model>,
+    #[module]
+    pub pos: Pos,
+    #[module]
+    pub blocks: Vec<EncoderBlockConfig<Model, NumHeads, F, Norm, FF>>,
+    #[module]
+    pub norm: Norm,
+    /// `None` when tied to the weight of `embedding`.
+    #[module]
+    pub head: Option<MatMulConfig<Model, Vocab>>,
+}
+
+impl<Vocab: Dim, Model: Dim, NumHeads: Dim, F: Dim> DecoderOnlyConfig<Vocab, Model, NumHeads, F> {
+    pub fn new(
+        vocab: Vocab,
+        model: Model,
+        num_heads: NumHeads,
+        f: F,
+        num_layers: usize,
+        max_len: usize,
+    ) -> Self {
+        let mut blocks = Vec::with_capacity(num_layers);
+        for _ in 0..num_layers {
+            let mut block = EncoderBlockConfig::new(model, num_heads, f)
+                .with_norm_placement(NormPlacement::Pre);
+            block.self_attn.0 = block.self_attn.0.with_causal(true);
+            blocks.push(block);
+        }
+        DecoderOnlyConfig {
+            embedding: EmbeddingConfig::new(vocab, model),
+            pos: LearnedPositionalEmbeddingConfig { max_len, model },
+            blocks,
+            norm: LayerNorm1DConfig(model),
+            head: Some(MatMulConfig {
+                inp: model,
+                out: vocab,
+            }),
+        }
+    }
+}
+
+impl<
+        Vocab: Dim,
+        Model: Dim,
+        NumHeads: Dim,
+        F: Dim,
+        Norm: std::fmt::Debug,
+        FF: std::fmt::Debug,
+        Pos: std::fmt::Debug,
+    > DecoderOnlyConfig<Vocab, Model, NumHeads, F, Norm, FF, Pos>
+{
+    /// Computes the logits with the transposed token embedding instead of a separate head.
+    pub fn with_tied_embeddings(mut self) -> Self {
+        self.head = None;
+        self
+    }
+
+    /// Replaces all normalization layers, including the final one, with `norm`.
+    pub fn with_norm<N: Clone + std::fmt::Debug>(
+        self,
+        norm: N,
+    ) -> DecoderOnlyConfig<Vocab, Model, NumHeads, F, N, FF, Pos> {
+        DecoderOnlyConfig {
+            embedding: self.embedding,
+            pos: self.pos,
+            blocks: self
+                .blocks
+                .into_iter()
+                .map(|b| b.with_norm(norm.clone()))
+                .collect(),
+            norm,
+            head: self.head,
+        }
+    }
+
+    /// Replaces the feed forward of every block with `ff`.
+    pub fn with_ff<G: Clone + std::fmt::Debug>(
+        self,
+        ff: G,
+    ) -> DecoderOnlyConfig<Vocab, Model, NumHeads, F, Norm, G, Pos> {
+        DecoderOnlyConfig {
+            embedding: self.embedding,
+            pos: self.pos,
+            blocks: self
+                .blocks
+                .into_iter()
+                .map(|b| b.with_ff(ff.clone()))
+                .collect(),
+            norm: self.norm,
+            head: self.head,
+        }
+    }
+
+    /// Replaces the position embedding with `pos`, e.g. [SinusoidalPositionalEncodingConfig].
+    pub fn with_pos<P: std::fmt::Debug>(
+        self,
+        pos: P,
+    ) -> DecoderOnlyConfig<Vocab, Model, NumHeads, F, Norm, FF, P> {
+        DecoderOnlyConfig {
+            embedding: self.embedding,
+            pos,
+            blocks: self.blocks,
+            norm: self.norm,
+            head: self.head,
+        }
+    }
+
+    pub fn with_norm_placement(mut self, norm_placement: NormPlacement) -> Self {
+        self.blocks = self
+            .blocks
+            .into_iter()
+            .map(|b| b.with_norm_placement(norm_placement))
+            .collect();
+        self
+    }
+
+    /// Sets the probability of all dropout in every block.
+    pub fn with_dropout(mut self, p: f64) -> Self
+    where
+        FF: WithDropout,
+    {
+        self.blocks = self.blocks.into_iter().map(|b| b.with_dropout(p)).collect();
+        self
+    }
+}
+
+impl<
+        V: Dim,
+        M: Dim,
+        H: Dim,
+        F: Dim,
+        N: std::fmt::Debug,
+        FF: std::fmt::Debug,
+        P: std::fmt::Debug,
+        E: Dtype,
+        D: Device<E>,
+    > DecoderOnly<V, M, H, F, N, FF, P, E, D>
+{
+    fn try_head<B: Dim, S: Dim, T: Tape<E, D>>(
+        &self,
+        x: Tensor<(B, S, M), E, D, T>,
+    ) -> Result<Tensor<(B, S, V), E, D, T>, D::Err> {
+        match &self.head {
+            Some(head) => x.try_matmul(head.weight.clone()),
+            None => {
+                let weight = self
+                    .embedding
+                    .weight
+                    .retaped::<T>()
+                    .try_permute::<_, Axes2<1, 0>>()?;
+                x.try_matmul(weight)
+            }
+        }
+    }
+}
+
+impl<
+        V: Dim,
+        M: Dim,
+        H: Dim,
+        F: Dim,
+        N: std::fmt::Debug,
+        FF: std::fmt::Debug,
+        P: std::fmt::Debug,
+        E: Dtype,
+        D: Device<E>,
+        B: Dim,
+        S: Dim,
+        T: Tape<E, D>,
+    > Module<Tensor<(B, S), usize, D, T>> for DecoderOnly<V, M, H, F, N, FF, P, E, D>
+where
+    N: BuildOnDevice<E, D>,
+    FF: BuildOnDevice<E, D>,
+    P: BuildOnDevice<E, D>,
+    P::Built:
+        Module<Tensor<(B, S, M), E, D, T>, Output = Tensor<(B, S, M), E, D, T>, Error = D::Err>,
+    Vec<EncoderBlock<M, H, F, N, FF, E, D>>:
+        Module<Tensor<(B, S, M), E, D, T>, Output = Tensor<(B, S, M), E, D, T>, Error = D::Err>,
+    N::Built:
+        Module<Tensor<(B, S, M), E, D, T>, Output = Tensor<(B, S, M), E, D, T>, Error = D::Err>,
+{
+    type Output = Tensor<(B, S, V), E, D, T>;
+    type Error = D::Err;
+
+    fn try_forward(&self, x: Tensor<(B, S), usize, D, T>) -> Result<Self::Output, D::Err> {
+        let x = self.embedding.try_forward(x)?;
+        let x = self.pos.try_forward(x)?;
+        let x = self.blocks.try_forward(x)?;
+        let x = self.norm.try_forward(x)?;
+        self.try_head(x)
+    }
+
+    fn try_forward_mut(&mut self, x: Tensor<(B, S), usize, D, T>) -> Result<Self::Output, D::Err> {
+        let x = self.embedding.try_forward_mut(x)?;
+        let x = self.pos.try_forward_mut(x)?;
+        let x = self.blocks.try_forward_mut(x)?;
+        let x = self.norm.try_forward_mut(x)?;
+        self.try_head(x)
+    }
+}
@@ -0,0 +1,154 @@
+use crate::*;
+use dfdx::{
+    shapes::{Dim, Dtype, HasShape, Shape},
+    tensor::{Tape, Tensor},
+    tensor_ops::{BroadcastTo, Device, TryMul},
+};
+
+/// Randomly zeroes each element with probability `p`, and scales the rest by `1 / (1 - p)`.
+///
+/// Like [BatchNorm2D], this is only active in [Module::try_forward_mut], when in training mode
+/// (see [SetTraining]). [Module::try_forward] is always the identity.
+#[derive(Debug, Clone, Copy, CustomModule)]
+pub struct Dropout {
+    pub p: f64,
+    #[training]
+    pub training: bool,
+}
+
+impl Dropout {
+    pub fn new(p: f64) -> Self {
+        Self { p, training: true }
+    }
+
+    /// dropout forward for training
+    pub fn train_fwd<S: Shape, E: Dtype, D: Device<E>, T: Tape<E, D>>(
+        &self,
+        x: Tensor<S, E, D, T>,
+    ) -> Result<Tensor<S, E, D, T>, D::Err> {
+        if self.p == 0.0 {
+            Ok(x)
+        } else {
+            x.try_dropout(self.p)
+        }
+    }
+}
+
+/// No dropout (`p = 0.0`).
+impl Default for Dropout {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+impl<S: Shape, E: Dtype, D: Device<E>, T: Tape<E, D>> Module<Tensor<S, E, D, T>> for Dropout {
+    type Output = Tensor<S, E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<S, E, D, T>) -> Result<Self::Output, Self::Error> {
+        Ok(x)
+    }
+    fn try_forward_mut(&mut self, x: Tensor<S, E, D, T>) -> Result<Self::Output, Self::Error> {
+        if self.training {
+            self.train_fwd(x)
+        } else {
+            Ok(x)
+        }
+    }
+}
+
+/// [Dropout] with `p = 1 / N`, for when the probability is known at compile time.
+#[derive(Debug, Clone, Copy, CustomModule)]
+pub struct DropoutOneIn<const N: usize> {
+    #[training]
+    pub training: bool,
+}
+
+impl<const N: usize> Default for DropoutOneIn<N> {
+    fn default() -> Self {
+        Self { training: true }
+    }
+}
+
+impl<const N: usize, S: Shape, E: Dtype, D: Device<E>, T: Tape<E, D>> Module<Tensor<S, E, D, T>>
+    for DropoutOneIn<N>
+{
+    type Output = Tensor<S, E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<S, E, D, T>) -> Result<Self::Output, Self::Error> {
+        Ok(x)
+    }
+    fn try_forward_mut(&mut self, x: Tensor<S, E, D, T>) -> Result<Self::Output, Self::Error> {
+        if self.training {
+            Dropout::new(1.0 / N as f64).train_fwd(x)
+        } else {
+            Ok(x)
+        }
+    }
+}
+
+/// Randomly zeroes entire channels of an image with probability `p`, and scales the rest
+/// by `1 / (1 - p)`. Same train/eval behavior as [Dropout].
+#[derive(Debug, Clone, Copy, CustomModule)]
+pub struct Dropout2D {
+    pub p: f64,
+    #[training]
+    pub training: bool,
+}
+
+impl Dropout2D {
+    pub fn new(p: f64) -> Self {
+        Self { p, training: true }
+    }
+}
+
+/// No dropout (`p = 0.0`).
+impl Default for Dropout2D {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+impl<C: Dim, H: Dim, W: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<Tensor<(C, H, W), E, D, T>> for Dropout2D
+{
+    type Output = Tensor<(C, H, W), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<(C, H, W), E, D, T>) -> Result<Self::Output, Self::Error> {
+        Ok(x)
+    }
+    fn try_forward_mut(
+        &mut self,
+        x: Tensor<(C, H, W), E, D, T>,
+    ) -> Result<Self::Output, Self::Error> {
+        if !self.training || self.p == 0.0 {
+            return Ok(x);
+        }
+        let shape = *x.shape();
+        let mask = x.device().try_ones_like(&(shape.0,))?.try_dropout(self.p)?;
+        x.try_mul(mask.try_broadcast_like(&shape)?)
+    }
+}
+
+impl<B: Dim, C: Dim, H: Dim, W: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<Tensor<(B, C, H, W), E, D, T>> for Dropout2D
+{
+    type Output = Tensor<(B, C, H, W), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<(B, C, H, W), E, D, T>) -> Result<Self::Output, Self::Error> {
+        Ok(x)
+    }
+    fn try_forward_mut(
+        &mut self,
+        x: Tensor<(B, C, H, W), E, D, T>,
+    ) -> Result<Self::Output, Self::Error> {
+        if !self.training || self.p == 0.0 {
+            return Ok(x);
+        }
+        let shape = *x.shape();
+        let mask = x
+            .device()
+            .try_ones_like(&(shape.0, shape.1))?
+            .try_dropout(self.p)?;
+        x.try_mul(mask.try_broadcast_like(&shape)?)
+    }
+}
@@ -0,0 +1,133 @@
+use crate::*;
+use dfdx::prelude::*;
+use rand_distr::{Distribution, StandardNormal};
+
+#[derive(Default, Clone, Copy, Debug)]
+pub struct EmbeddingConfig<Vocab: Dim, Model: Dim> {
+    pub vocab: Vocab,
+    pub model: Model,
+    /// Index of the padding token. Its row is all zeros, and is never updated by training.
+    pub padding_idx: Option<usize>,
+}
+
+pub type EmbeddingConstConfig<const V: usize, const M: usize> = EmbeddingConfig<Const<V>, Const<M>>;
+
+impl<Vocab: Dim, Model: Dim> EmbeddingConfig<Vocab, Model> {
+    pub fn new(vocab: Vocab, model: Model) -> Self {
+        Self {
+            vocab,
+            model,
+            padding_idx: None,
+        }
+    }
+
+    pub fn with_padding_idx(mut self, padding_idx: usize) -> Self {
+        self.padding_idx = Some(padding_idx);
+        self
+    }
+}
+
+impl<V: Dim, M: Dim, E: Dtype, D: Device<E>> BuildOnDevice<E, D> for EmbeddingConfig<V, M> {
+    type Built = Embedding<V, M, E, D>;
+    fn try_build_on_device(&self, device: &D) -> Result<Self::Built, D::Err> {
+        let padding_mask = match self.padding_idx {
+            None => None,
+            Some(padding_idx) => {
+                assert!(padding_idx < self.vocab.size());
+                let mut mask = vec![E::from_f64(1.0).unwrap(); self.vocab.size()];
+                mask[padding_idx] = E::default();
+                Some(device.try_tensor_from_vec(mask, (self.vocab,))?)
+            }
+        };
+        Ok(Embedding {
+            weight: device.try_zeros_like(&(self.vocab, self.model))?,
+            padding_idx: self.padding_idx,
+            padding_mask,
+        })
+    }
+}
+
+/// Maps each token index to a learned vector of size `M`, i.e. a row of `weight`.
+///
+/// Pretrained vectors can be loaded from safetensors as the `weight` tensor of shape `(V, M)`.
+#[derive(Clone, Debug, VisitTensors, SaveSafeTensors, LoadSafeTensors, SetTraining)]
+pub struct Embedding<V: Dim, M: Dim, Elem: Dtype, Dev: Device<Elem>> {
+    #[param]
+    #[serialize]
+    pub weight: Tensor<(V, M), Elem, Dev>,
+    padding_idx: Option<usize>,
+    /// `0` for the padding token and `1` for every other token. Multiplying the embeddings by
+    /// this stops gradients from flowing to the padding row.
+    padding_mask: Option<Tensor<(V,), Elem, Dev>>,
+}
+
+impl<V: Dim, M: Dim, E: Dtype, D: Device<E>> ResetParams<E, D> for Embedding<V, M, E, D>
+where
+    StandardNormal: Distribution<E>,
+{
+    fn try_reset_params(&mut self) -> Result<(), D::Err> {
+        self.weight.try_fill_with_distr(StandardNormal)?;
+        if let Some(padding_idx) = self.padding_idx {
+            let m = self.weight.shape().1.size();
+            let mut weight = self.weight.as_vec();
+            for w in weight[padding_idx * m..(padding_idx + 1) * m].iter_mut() {
+                *w = E::default();
+            }
+            self.weight.copy_from(&weight);
+        }
+        Ok(())
+    }
+}
+
+impl<V: Dim, M: Dim, E: Dtype, D: Device<E>> Embedding<V, M, E, D> {
+    /// The padding token set by [EmbeddingConfig::with_padding_idx].
+    pub fn padding_idx(&self) -> Option<usize> {
+        self.padding_idx
+    }
+}
+
+impl<V: Dim, M: Dim, S: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<Tensor<(S,), usize, D, T>> for Embedding<V, M, E, D>
+{
+    type Output = Tensor<(S, M), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, input: Tensor<(S,), usize, D, T>) -> Result<Self::Output, D::Err> {
+        let (input, tape) = input.split_tape();
+        let x = self
+            .weight
+            .clone()
+            .put_tape(tape)
+            .try_gather(input.clone())?;
+        match &self.padding_mask {
+            None => Ok(x),
+            Some(mask) => {
+                let shape = *x.shape();
+                let mask = mask.clone().try_gather(input)?;
+                x.try_mul(mask.try_broadcast_like(&shape)?)
+            }
+        }
+    }
+}
+
+impl<V: Dim, M: Dim, B: Dim, S: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<Tensor<(B, S), usize, D, T>> for Embedding<V, M, E, D>
+{
+    type Output = Tensor<(B, S, M), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, input: Tensor<(B, S), usize, D, T>) -> Result<Self::Output, D::Err> {
+        let (input, tape) = input.split_tape();
+        let x = self
+            .weight
+            .clone()
+            .put_tape(tape)
+            .try_gather(input.clone())?;
+        match &self.padding_mask {
+            None => Ok(x),
+            Some(mask) => {
+                let shape = *x.shape();
+                let mask = mask.clone().try_gather(input)?;
+                x.try_mul(mask.try_broadcast_like(&shape)?)
+            }
+        }
+    }
+}
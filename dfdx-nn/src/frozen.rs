@@ -0,0 +1,116 @@
+use crate::*;
+use dfdx::{
+    shapes::{Dtype, Shape},
+    tensor::Tensor,
+    tensor_ops::Device,
+};
+
+/// Wraps a module so that its params are not trained.
+///
+/// The wrapped module is saved/loaded with safetensors under the same names as if it were not
+/// wrapped (so pretrained weights load as is), and forwards whatever input the wrapped module
+/// takes as is, so gradients still flow through it to the layers before it. However:
+/// - All of its `#[param]`s are visited as [TensorKind::Buffer]s, so optimizers skip them
+///   (without reporting them as `UnusedTensors`), and [ZeroGrads]/[ClipGrads] ignore them.
+/// - It is always in eval mode: [SetTraining] can't turn it on, and [Module::try_forward_mut]
+///   runs the wrapped module's [Module::try_forward]. So e.g. the running statistics of a
+///   `BatchNorm2D` inside are never updated.
+///
+/// E.g. to only fine-tune the head of a pretrained model:
+/// ```ignore
+/// type Model = (Frozen<Backbone>, LinearConstConfig<512, 10>);
+/// ```
+#[derive(Default, Clone, Debug, ResetParams)]
+pub struct Frozen<T>(#[module] pub T);
+
+impl<E: Dtype, D: Device<E>, T: BuildOnDevice<E, D>> BuildOnDevice<E, D> for Frozen<T> {
+    type Built = Frozen<T::Built>;
+    fn try_build_on_device(&self, device: &D) -> Result<Self::Built, <D>::Err> {
+        let t = self.0.try_build_on_device(device)?;
+        Ok(Frozen(t))
+    }
+}
+
+impl<X, M: Module<X>> Module<X> for Frozen<M> {
+    type Output = M::Output;
+    type Error = M::Error;
+    fn try_forward(&self, x: X) -> Result<Self::Output, Self::Error> {
+        self.0.try_forward(x)
+    }
+    fn try_forward_mut(&mut self, x: X) -> Result<Self::Output, Self::Error> {
+        self.0.try_forward(x)
+    }
+}
+
+impl<T: SetTraining> SetTraining for Frozen<T> {
+    fn set_training(&mut self, _training: bool) {
+        self.0.set_training(false)
+    }
+}
+
+impl<T: SaveSafeTensors> SaveSafeTensors for Frozen<T> {
+    fn write_safetensors(
+        &self,
+        location: &str,
+        tensors: &mut Vec<(String, safetensors::Dtype, Vec<usize>, Vec<u8>)>,
+    ) {
+        self.0.write_safetensors(location, tensors)
+    }
+}
+
+impl<T: LoadSafeTensors> LoadSafeTensors for Frozen<T> {
+    fn read_safetensors<'a>(
+        &mut self,
+        location: &str,
+        tensors: &safetensors::SafeTensors<'a>,
+    ) -> Result<(), safetensors::SafeTensorError> {
+        self.0.read_safetensors(location, tensors)
+    }
+}
+
+struct FreezeVisitor<'a, V>(&'a mut V);
+
+impl<'a, E: Dtype, D: Device<E>, V: TensorVisitor<E, D>> TensorVisitor<E, D>
+    for FreezeVisitor<'a, V>
+{
+    fn visit<S: Shape>(
+        &mut self,
+        name: &str,
+        _: TensorKind,
+        t: &Tensor<S, E, D>,
+    ) -> Result<(), D::Err> {
+        self.0.visit(name, TensorKind::Buffer, t)
+    }
+}
+
+impl<'a, E: Dtype, D: Device<E>, V: TensorVisitorMut<E, D>> TensorVisitorMut<E, D>
+    for FreezeVisitor<'a, V>
+{
+    fn visit_mut<S: Shape>(
+        &mut self,
+        name: &str,
+        _: TensorKind,
+        t: &mut Tensor<S, E, D>,
+    ) -> Result<(), D::Err> {
+        self.0.visit_mut(name, TensorKind::Buffer, t)
+    }
+}
+
+impl<E: Dtype, D: Device<E>, T: VisitTensors<E, D>> VisitTensors<E, D> for Frozen<T> {
+    fn try_visit_tensors<V: TensorVisitor<E, D>>(
+        &self,
+        location: &str,
+        visitor: &mut V,
+    ) -> Result<(), D::Err> {
+        self.0
+            .try_visit_tensors(location, &mut FreezeVisitor(visitor))
+    }
+    fn try_visit_tensors_mut<V: TensorVisitorMut<E, D>>(
+        &mut self,
+        location: &str,
+        visitor: &mut V,
+    ) -> Result<(), D::Err> {
+        self.0
+            .try_visit_tensors_mut(location, &mut FreezeVisitor(visitor))
+    }
+}
@@ -6,7 +6,7 @@ use dfdx::{
 };
 
 #[derive(
-    Default, Clone, Debug, ResetParams, ZeroGrads, UpdateParams, LoadSafeTensors, SaveSafeTensors,
+    Default, Clone, Debug, ResetParams, VisitTensors, LoadSafeTensors, SaveSafeTensors, SetTraining,
 )]
 pub struct GeneralizedAdd<T, U>(
     #[module]
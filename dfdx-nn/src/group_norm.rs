@@ -0,0 +1,100 @@
+use crate::*;
+use dfdx::prelude::*;
+
+#[derive(Default, Clone, Copy, Debug)]
+pub struct GroupNormConfig<G: Dim, C: Dim> {
+    pub groups: G,
+    pub channels: C,
+}
+
+pub type GroupNormConstConfig<const G: usize, const C: usize> = GroupNormConfig<Const<G>, Const<C>>;
+
+impl<G: Dim, C: Dim, E: Dtype, D: Device<E>> crate::BuildOnDevice<E, D> for GroupNormConfig<G, C> {
+    type Built = GroupNorm<G, C, E, D>;
+    fn try_build_on_device(&self, device: &D) -> Result<Self::Built, D::Err> {
+        assert_eq!(self.channels.size() % self.groups.size(), 0);
+        Ok(GroupNorm {
+            gamma: device.try_ones_like(&(self.channels,))?,
+            beta: device.try_zeros_like(&(self.channels,))?,
+            groups: self.groups,
+            epsilon: 1e-5,
+        })
+    }
+}
+
+/// Splits the channels of images into `G` groups, and normalizes each group over its
+/// channels & pixels, followed by a per channel affine transform.
+///
+/// Unlike [BatchNorm2D] the statistics don't depend on the batch, so this works with small batches.
+#[derive(Clone, Debug, VisitTensors, SaveSafeTensors, LoadSafeTensors, SetTraining)]
+pub struct GroupNorm<G: Dim, C: Dim, Elem: Dtype, Dev: Device<Elem>> {
+    #[param]
+    #[serialize]
+    pub gamma: Tensor<(C,), Elem, Dev>,
+    #[param]
+    #[serialize]
+    pub beta: Tensor<(C,), Elem, Dev>,
+    pub groups: G,
+    #[serialize]
+    pub epsilon: f64,
+}
+
+impl<G: Dim, C: Dim, E: Dtype, D: Device<E>> crate::ResetParams<E, D> for GroupNorm<G, C, E, D> {
+    fn try_reset_params(&mut self) -> Result<(), D::Err> {
+        self.gamma.try_fill_with_ones()?;
+        self.beta.try_fill_with_zeros()
+    }
+}
+
+impl<G: Dim, C: Dim, H: Dim, W: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    crate::Module<Tensor<(C, H, W), E, D, T>> for GroupNorm<G, C, E, D>
+{
+    type Output = Tensor<(C, H, W), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<(C, H, W), E, D, T>) -> Result<Self::Output, Self::Error> {
+        let shape = *x.shape();
+        let (c, h, w) = shape;
+        let g = self.groups.size();
+        let x = x
+            .try_reshape_like(&(g, c.size() / g, h, w))?
+            .try_normalize::<Axes3<1, 2, 3>>(self.epsilon)?
+            .try_reshape_like(&shape)?;
+        let x = self
+            .gamma
+            .retaped::<T>()
+            .try_broadcast_like(&shape)?
+            .try_mul(x)?;
+        self.beta
+            .retaped::<T>()
+            .try_broadcast_like(&shape)?
+            .try_add(x)
+    }
+}
+
+impl<G: Dim, Batch: Dim, C: Dim, H: Dim, W: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    crate::Module<Tensor<(Batch, C, H, W), E, D, T>> for GroupNorm<G, C, E, D>
+{
+    type Output = Tensor<(Batch, C, H, W), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(
+        &self,
+        x: Tensor<(Batch, C, H, W), E, D, T>,
+    ) -> Result<Self::Output, Self::Error> {
+        let shape = *x.shape();
+        let (b, c, h, w) = shape;
+        let g = self.groups.size();
+        let x = x
+            .try_reshape_like(&(b, g, c.size() / g, h, w))?
+            .try_normalize::<Axes3<2, 3, 4>>(self.epsilon)?
+            .try_reshape_like(&shape)?;
+        let x = self
+            .gamma
+            .retaped::<T>()
+            .try_broadcast_like(&shape)?
+            .try_mul(x)?;
+        self.beta
+            .retaped::<T>()
+            .try_broadcast_like(&shape)?
+            .try_add(x)
+    }
+}
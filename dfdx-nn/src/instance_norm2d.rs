@@ -0,0 +1,83 @@
+use crate::*;
+use dfdx::prelude::*;
+
+#[derive(Default, Clone, Copy, Debug)]
+#[repr(transparent)]
+pub struct InstanceNorm2DConfig<C: Dim>(pub C);
+
+pub type InstanceNorm2DConstConfig<const C: usize> = InstanceNorm2DConfig<Const<C>>;
+
+impl<C: Dim, E: Dtype, D: Device<E>> crate::BuildOnDevice<E, D> for InstanceNorm2DConfig<C> {
+    type Built = InstanceNorm2D<C, E, D>;
+    fn try_build_on_device(&self, device: &D) -> Result<Self::Built, D::Err> {
+        Ok(InstanceNorm2D {
+            gamma: device.try_ones_like(&(self.0,))?,
+            beta: device.try_zeros_like(&(self.0,))?,
+            epsilon: 1e-5,
+        })
+    }
+}
+
+/// Normalizes each channel of each image over its pixels, followed by a per channel
+/// affine transform.
+#[derive(Clone, Debug, VisitTensors, SaveSafeTensors, LoadSafeTensors, SetTraining)]
+pub struct InstanceNorm2D<C: Dim, Elem: Dtype, Dev: Device<Elem>> {
+    #[param]
+    #[serialize]
+    pub gamma: Tensor<(C,), Elem, Dev>,
+    #[param]
+    #[serialize]
+    pub beta: Tensor<(C,), Elem, Dev>,
+    #[serialize]
+    pub epsilon: f64,
+}
+
+impl<C: Dim, E: Dtype, D: Device<E>> crate::ResetParams<E, D> for InstanceNorm2D<C, E, D> {
+    fn try_reset_params(&mut self) -> Result<(), D::Err> {
+        self.gamma.try_fill_with_ones()?;
+        self.beta.try_fill_with_zeros()
+    }
+}
+
+impl<C: Dim, H: Dim, W: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    crate::Module<Tensor<(C, H, W), E, D, T>> for InstanceNorm2D<C, E, D>
+{
+    type Output = Tensor<(C, H, W), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<(C, H, W), E, D, T>) -> Result<Self::Output, Self::Error> {
+        let shape = *x.shape();
+        let x = x.try_normalize::<Axes2<1, 2>>(self.epsilon)?;
+        let x = self
+            .gamma
+            .retaped::<T>()
+            .try_broadcast_like(&shape)?
+            .try_mul(x)?;
+        self.beta
+            .retaped::<T>()
+            .try_broadcast_like(&shape)?
+            .try_add(x)
+    }
+}
+
+impl<Batch: Dim, C: Dim, H: Dim, W: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    crate::Module<Tensor<(Batch, C, H, W), E, D, T>> for InstanceNorm2D<C, E, D>
+{
+    type Output = Tensor<(Batch, C, H, W), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(
+        &self,
+        x: Tensor<(Batch, C, H, W), E, D, T>,
+    ) -> Result<Self::Output, Self::Error> {
+        let shape = *x.shape();
+        let x = x.try_normalize::<Axes2<2, 3>>(self.epsilon)?;
+        let x = self
+            .gamma
+            .retaped::<T>()
+            .try_broadcast_like(&shape)?
+            .try_mul(x)?;
+        self.beta
+            .retaped::<T>()
+            .try_broadcast_like(&shape)?
+            .try_add(x)
+    }
+}
@@ -1,43 +1,104 @@
 #![feature(generic_const_exprs)]
 
+mod activations;
+mod adam;
+mod adaptive_pool_2d;
+mod avg_pool_2d;
 mod avg_pool_global;
+mod batch_norm1d;
 mod batch_norm2d;
 mod bias1d;
 mod bias2d;
+mod conv1d;
 mod conv2d;
+mod conv_trans2d;
+mod decoder_only;
+mod dropout;
+mod embedding;
 mod flatten2d;
+mod frozen;
 mod generalized_add;
+mod group_norm;
+mod instance_norm2d;
 mod layer_norm1d;
 mod linear;
+mod lr_scheduler;
 mod matmul;
 mod max_pool_2d;
+mod max_pool_global;
 mod multi_head_attention;
+mod param_groups;
+mod positional_encoding;
+mod prelu;
 mod relu;
 mod reshape;
 mod residual_add;
+mod rms_norm1d;
+mod rmsprop;
+mod sequence_pool;
 mod sgd;
 mod transformer;
+mod transformer_encoder;
 
 pub use dfdx_nn_core::*;
 pub use dfdx_nn_derives::*;
 
+pub use activations::{
+    FastGeLU, GeLU, HardSwish, LeakyReLU, LogSoftmax, Mish, SiLU, Sigmoid, Softmax, Softplus,
+    Swish, Tanh, ELU,
+};
+pub use adam::{Adam, AdamW};
+pub use adaptive_pool_2d::{
+    AdaptiveAvgPool2D, AdaptiveAvgPool2DConst, AdaptiveMaxPool2D, AdaptiveMaxPool2DConst,
+};
+pub use avg_pool_2d::{AvgPool2D, AvgPool2DConst};
 pub use avg_pool_global::AvgPoolGlobal;
+pub use batch_norm1d::{BatchNorm1D, BatchNorm1DConfig, BatchNorm1DConstConfig};
 pub use batch_norm2d::{BatchNorm2D, BatchNorm2DConfig, BatchNorm2DConstConfig};
 pub use bias1d::{Bias1D, Bias1DConfig, Bias1DConstConfig};
 pub use bias2d::{Bias2D, Bias2DConfig, Bias2DConstConfig};
+pub use conv1d::{Conv1D, Conv1DConfig, Conv1DConstConfig};
 pub use conv2d::{Conv2D, Conv2DConfig, Conv2DConstConfig};
+pub use conv_trans2d::{ConvTrans2D, ConvTrans2DConfig, ConvTrans2DConstConfig};
+pub use decoder_only::{DecoderOnly, DecoderOnlyConfig};
+pub use dropout::{Dropout, Dropout2D, DropoutOneIn};
+pub use embedding::{Embedding, EmbeddingConfig, EmbeddingConstConfig};
 pub use flatten2d::Flatten2D;
+pub use frozen::Frozen;
 pub use generalized_add::GeneralizedAdd;
+pub use group_norm::{GroupNorm, GroupNormConfig, GroupNormConstConfig};
+pub use instance_norm2d::{InstanceNorm2D, InstanceNorm2DConfig, InstanceNorm2DConstConfig};
 pub use layer_norm1d::{LayerNorm1D, LayerNorm1DConfig, LayerNorm1DConstConfig};
 pub use linear::{Linear, LinearConfig, LinearConstConfig};
+pub use lr_scheduler::{
+    CosineAnnealingLr, ExponentialLr, LinearWarmup, LrScheduler, MultiStepLr, OneCycleLr,
+    SequentialLr, StepLr,
+};
 pub use matmul::{MatMul, MatMulConfig, MatMulConstConfig};
 pub use max_pool_2d::{MaxPool2D, MaxPool2DConst};
-pub use multi_head_attention::{MultiHeadAttention, MultiHeadAttentionConfig};
+pub use max_pool_global::MaxPoolGlobal;
+pub use multi_head_attention::{
+    AttentionMask, KVCache, MultiHeadAttention, MultiHeadAttentionConfig,
+};
+pub use param_groups::{ParamFilter, ParamGroup};
+pub use positional_encoding::{
+    LearnedPositionalEmbedding, LearnedPositionalEmbeddingConfig,
+    LearnedPositionalEmbeddingConstConfig, SinusoidalPositionalEncoding,
+    SinusoidalPositionalEncodingConfig, SinusoidalPositionalEncodingConstConfig,
+};
+pub use prelu::{PReLU, PReLUConfig};
 pub use relu::ReLU;
 pub use reshape::Reshape;
 pub use residual_add::ResidualAdd;
+pub use rms_norm1d::{RMSNorm1D, RMSNorm1DConfig, RMSNorm1DConstConfig};
+pub use rmsprop::RMSprop;
+pub use sequence_pool::{ClsPool, MeanPool};
 pub use sgd::Sgd;
 pub use transformer::{
-    DecoderBlock, DecoderBlockConfig, EncoderBlock, EncoderBlockConfig, Transformer,
-    TransformerConfig,
+    DecoderBlock, DecoderBlockConfig, EncoderBlock, EncoderBlockConfig, FeedForward,
+    FeedForwardConfig, GatedFeedForward, GatedFeedForwardConfig, GeGLUConfig, NormPlacement,
+    SwiGLUConfig, Transformer, TransformerConfig, WithDropout,
+};
+pub use transformer_encoder::{
+    EmbeddedDtype, PoolerConfig, TransformerEncoder, TransformerEncoderConfig,
 };
@@ -0,0 +1,182 @@
+use crate::HasLearningRate;
+
+/// Computes the learning rate to use for a given optimizer step.
+///
+/// Schedules are pure functions of the step, so they can be freely composed
+/// (see [SequentialLr]). Call [LrScheduler::apply] before each optimizer update:
+/// ```ignore
+/// for step in 0..num_steps {
+///     sched.apply(step, &mut opt);
+///     opt.update(&mut model, &grads)?;
+/// }
+/// ```
+pub trait LrScheduler {
+    /// The learning rate for the `step`th (0 indexed) optimizer update.
+    fn lr(&self, step: usize) -> f64;
+
+    /// Sets the learning rate of `optimizer` to [LrScheduler::lr] at `step`.
+    fn apply<O: HasLearningRate>(&self, step: usize, optimizer: &mut O) {
+        *optimizer.learning_rate_mut() = self.lr(step);
+    }
+}
+
+/// `gamma^n`, with `n` clamped to `i32::MAX`. By then `gamma^n` has long
+/// underflowed to 0 (or overflowed) anyway.
+fn decay(gamma: f64, n: usize) -> f64 {
+    gamma.powi(i32::try_from(n).unwrap_or(i32::MAX))
+}
+
+/// Decays `base_lr` by `gamma` every `step_size` steps. Panics if `step_size` is 0.
+#[derive(Debug, Clone, Copy)]
+pub struct StepLr {
+    pub base_lr: f64,
+    pub step_size: usize,
+    pub gamma: f64,
+}
+
+impl LrScheduler for StepLr {
+    fn lr(&self, step: usize) -> f64 {
+        assert!(self.step_size > 0, "step_size must be at least 1");
+        self.base_lr * decay(self.gamma, step / self.step_size)
+    }
+}
+
+/// Decays `base_lr` by `gamma` once each milestone has been reached.
+#[derive(Debug, Clone)]
+pub struct MultiStepLr {
+    pub base_lr: f64,
+    pub milestones: Vec<usize>,
+    pub gamma: f64,
+}
+
+impl LrScheduler for MultiStepLr {
+    fn lr(&self, step: usize) -> f64 {
+        let num_passed = self.milestones.iter().filter(|&&m| m <= step).count();
+        self.base_lr * decay(self.gamma, num_passed)
+    }
+}
+
+/// Decays `base_lr` by `gamma` every step.
+#[derive(Debug, Clone, Copy)]
+pub struct ExponentialLr {
+    pub base_lr: f64,
+    pub gamma: f64,
+}
+
+impl LrScheduler for ExponentialLr {
+    fn lr(&self, step: usize) -> f64 {
+        self.base_lr * decay(self.gamma, step)
+    }
+}
+
+/// Anneals from `base_lr` to `min_lr` over `total_steps` following half a cosine wave.
+/// Stays at `min_lr` afterwards, so a `total_steps` of 0 is always `min_lr`.
+#[derive(Debug, Clone, Copy)]
+pub struct CosineAnnealingLr {
+    pub base_lr: f64,
+    pub min_lr: f64,
+    pub total_steps: usize,
+}
+
+impl LrScheduler for CosineAnnealingLr {
+    fn lr(&self, step: usize) -> f64 {
+        if step >= self.total_steps {
+            return self.min_lr;
+        }
+        let t = step as f64 / self.total_steps as f64;
+        let cos = (1.0 + (std::f64::consts::PI * t).cos()) / 2.0;
+        self.min_lr + (self.base_lr - self.min_lr) * cos
+    }
+}
+
+/// Linearly increases from `start_factor * base_lr` to `base_lr` over `warmup_steps`.
+/// Stays at `base_lr` afterwards, so a `warmup_steps` of 0 is no warmup.
+#[derive(Debug, Clone, Copy)]
+pub struct LinearWarmup {
+    pub base_lr: f64,
+    pub start_factor: f64,
+    pub warmup_steps: usize,
+}
+
+impl LrScheduler for LinearWarmup {
+    fn lr(&self, step: usize) -> f64 {
+        if step >= self.warmup_steps {
+            return self.base_lr;
+        }
+        let t = step as f64 / self.warmup_steps as f64;
+        self.base_lr * (self.start_factor + (1.0 - self.start_factor) * t)
+    }
+}
+
+/// The 1cycle policy: cosine warmup from `max_lr / div_factor` to `max_lr` for the
+/// first `pct_start` of `total_steps`, then cosine annealing down to
+/// `max_lr / (div_factor * final_div_factor)`.
+#[derive(Debug, Clone, Copy)]
+pub struct OneCycleLr {
+    pub max_lr: f64,
+    pub total_steps: usize,
+    pub pct_start: f64,
+    pub div_factor: f64,
+    pub final_div_factor: f64,
+}
+
+impl OneCycleLr {
+    pub fn new(max_lr: f64, total_steps: usize) -> Self {
+        Self {
+            max_lr,
+            total_steps,
+            pct_start: 0.3,
+            div_factor: 25.0,
+            final_div_factor: 1e4,
+        }
+    }
+}
+
+impl LrScheduler for OneCycleLr {
+    fn lr(&self, step: usize) -> f64 {
+        let initial_lr = self.max_lr / self.div_factor;
+        let min_lr = initial_lr / self.final_div_factor;
+        let warmup_steps = (self.pct_start * self.total_steps as f64) as usize;
+        if step < warmup_steps {
+            CosineAnnealingLr {
+                base_lr: self.max_lr,
+                min_lr: initial_lr,
+                total_steps: warmup_steps,
+            }
+            .lr(warmup_steps - step)
+        } else {
+            CosineAnnealingLr {
+                base_lr: self.max_lr,
+                min_lr,
+                total_steps: self.total_steps.saturating_sub(warmup_steps),
+            }
+            .lr(step - warmup_steps)
+        }
+    }
+}
+
+/// Uses `first` for steps before `milestone`, and `second` afterwards. `second` sees
+/// steps counted from `milestone`, so e.g. warmup followed by cosine decay is:
+/// ```ignore
+/// SequentialLr {
+///     first: LinearWarmup { base_lr: 1e-3, start_factor: 0.0, warmup_steps: 1000 },
+///     second: CosineAnnealingLr { base_lr: 1e-3, min_lr: 1e-5, total_steps: 9000 },
+///     milestone: 1000,
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SequentialLr<A, B> {
+    pub first: A,
+    pub second: B,
+    pub milestone: usize,
+}
+
+impl<A: LrScheduler, B: LrScheduler> LrScheduler for SequentialLr<A, B> {
+    fn lr(&self, step: usize) -> f64 {
+        if step < self.milestone {
+            self.first.lr(step)
+        } else {
+            self.second.lr(step - self.milestone)
+        }
+    }
+}
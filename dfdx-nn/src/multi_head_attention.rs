@@ -13,9 +13,21 @@ pub struct MultiHeadAttentionConfig<Embed: Dim, NumHeads: Dim, K: Dim = Embed, V
     pub w_v: LinearConfig<Embed, V>,
     #[module]
     pub w_o: LinearConfig<V, Embed>,
+    /// Applied to the attention weights in [Module::try_forward_mut].
+    #[module]
+    pub dropout: Dropout,
     pub num_heads: NumHeads,
     pub k_dim: K,
     pub v_dim: V,
+    /// Whether each query is masked from attending to keys after it. Used for autoregressive
+    /// self attention.
+    pub causal: bool,
+    /// The base `theta` of the rotary position embedding (RoPE) applied to the queries & keys
+    /// of each head, usually `10000.0`. `None` means no rotary embedding.
+    ///
+    /// Only applied by self attention (`x`, `(x, mask)` and `(x, cache)` inputs), where the
+    /// positions of the queries among the keys are known. The `(q, k, v)` inputs don't apply it.
+    pub rope_theta: Option<f64>,
 }
 
 impl<Embed: Dim, NumHeads: Dim, K: Dim, V: Dim> MultiHeadAttentionConfig<Embed, NumHeads, K, V> {
@@ -29,11 +41,120 @@ impl<Embed: Dim, NumHeads: Dim, K: Dim, V: Dim> MultiHeadAttentionConfig<Embed,
             w_k: LinearConfig::new(embed, k),
             w_v: LinearConfig::new(embed, v),
             w_o: LinearConfig::new(v, embed),
+            dropout: Default::default(),
             num_heads,
             k_dim: k,
             v_dim: v,
+            causal: false,
+            rope_theta: None,
+        }
+    }
+
+    pub fn with_causal(mut self, causal: bool) -> Self {
+        self.causal = causal;
+        self
+    }
+
+    pub fn with_rope(mut self, theta: f64) -> Self {
+        self.rope_theta = Some(theta);
+        self
+    }
+}
+
+/// Masks applied to the attention logits of [MultiHeadAttention] before the softmax, as the
+/// last element of the input tuple.
+///
+/// Both masks are stored as tensors that are added to the logits, so boolean masks (`true` means
+/// masked out) are converted to `-inf`/`0` when set. A query whose keys are all masked out
+/// attends to them uniformly, instead of producing NaNs.
+#[derive(Clone, Debug)]
+pub struct AttentionMask<B: Dim, S1: Dim, S2: Dim, E: Dtype, D: Device<E>> {
+    /// Added to the logits of every batch item and head.
+    pub attn: Option<Tensor<(S1, S2), E, D>>,
+    /// Added to the logits of each batch item's keys.
+    pub key_padding: Option<Tensor<(B, S2), E, D>>,
+}
+
+impl<B: Dim, S1: Dim, S2: Dim, E: Dtype + Float, D: Device<E>> AttentionMask<B, S1, S2, E, D> {
+    pub fn new() -> Self {
+        Self {
+            attn: None,
+            key_padding: None,
         }
     }
+
+    /// Sets an additive mask for every batch item and head.
+    pub fn with_attn(mut self, mask: Tensor<(S1, S2), E, D>) -> Self {
+        self.attn = Some(mask);
+        self
+    }
+
+    /// Sets a mask for every batch item and head, where `true` means the query can't attend
+    /// to the key.
+    pub fn with_bool_attn(self, masked: Tensor<(S1, S2), bool, D>) -> Self {
+        self.try_with_bool_attn(masked).unwrap()
+    }
+
+    /// Fallible version of [AttentionMask::with_bool_attn]
+    pub fn try_with_bool_attn(mut self, masked: Tensor<(S1, S2), bool, D>) -> Result<Self, D::Err> {
+        self.attn = Some(bool_to_additive(masked)?);
+        Ok(self)
+    }
+
+    /// Sets which keys of each batch item are padding (`true`), and can't be attended to.
+    pub fn with_key_padding(self, padding: Tensor<(B, S2), bool, D>) -> Self {
+        self.try_with_key_padding(padding).unwrap()
+    }
+
+    /// Fallible version of [AttentionMask::with_key_padding]
+    pub fn try_with_key_padding(
+        mut self,
+        padding: Tensor<(B, S2), bool, D>,
+    ) -> Result<Self, D::Err> {
+        self.key_padding = Some(bool_to_additive(padding)?);
+        Ok(self)
+    }
+}
+
+impl<B: Dim, S1: Dim, S2: Dim, E: Dtype + Float, D: Device<E>> Default
+    for AttentionMask<B, S1, S2, E, D>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn bool_to_additive<S: Shape, E: Dtype + Float, D: Device<E>>(
+    masked: Tensor<S, bool, D>,
+) -> Result<Tensor<S, E, D>, D::Err> {
+    let dev = masked.device().clone();
+    let shape = *masked.shape();
+    let zeros = dev.try_zeros_like(&shape)?;
+    let neg_inf = zeros.clone().try_add(E::neg_infinity())?;
+    masked.try_choose(neg_inf, zeros)
+}
+
+/// The keys & values (already projected by `w_k` & `w_v`) of the tokens that a self attention
+/// [MultiHeadAttention] has seen so far, for incremental decoding.
+///
+/// Passing `(x, cache)` to [MultiHeadAttention] attends the new tokens `x` to the cached ones
+/// and to themselves, and returns the output along with the cache extended by `x`. Start with
+/// `None` for the prompt.
+#[derive(Clone, Debug)]
+pub struct KVCache<B: Dim, K: Dim, V: Dim, E: Dtype, D: Device<E>> {
+    pub keys: Tensor<(B, usize, K), E, D>,
+    pub values: Tensor<(B, usize, V), E, D>,
+}
+
+impl<B: Dim, K: Dim, V: Dim, E: Dtype, D: Device<E>> KVCache<B, K, V, E, D> {
+    /// The number of cached tokens.
+    pub fn len(&self) -> usize {
+        self.keys.shape().1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 impl<M: Dim, H: Dim, K: Dim, V: Dim, E, D, S1, S2, T>
@@ -64,12 +185,87 @@ where
         assert_eq!(k.shape().0, v.shape().0);
         let (s1, m) = *q.shape();
         let s2 = k.shape().0;
-        let q = q.broadcast_like(&(Const::<1>, s1, m));
-        let k = k.broadcast_like(&(Const::<1>, s2, m));
-        let v = v.broadcast_like(&(Const::<1>, s2, m));
+        let q = q.try_broadcast_like(&(Const::<1>, s1, m))?;
+        let k = k.try_broadcast_like(&(Const::<1>, s2, m))?;
+        let v = v.try_broadcast_like(&(Const::<1>, s2, m))?;
         let out = self.try_forward((q, k, v))?;
         out.try_reshape_like(&(s1, m))
     }
+
+    fn try_forward_mut(
+        &mut self,
+        (q, k, v): (
+            Tensor<(S1, M), E, D, T>,
+            Tensor<(S2, M), E, D>,
+            Tensor<(S2, M), E, D>,
+        ),
+    ) -> Result<Self::Output, D::Err> {
+        assert_eq!(k.shape().0, v.shape().0);
+        let (s1, m) = *q.shape();
+        let s2 = k.shape().0;
+        let q = q.try_broadcast_like(&(Const::<1>, s1, m))?;
+        let k = k.try_broadcast_like(&(Const::<1>, s2, m))?;
+        let v = v.try_broadcast_like(&(Const::<1>, s2, m))?;
+        let out = self.try_forward_mut((q, k, v))?;
+        out.try_reshape_like(&(s1, m))
+    }
+}
+
+impl<M: Dim, H: Dim, K: Dim, V: Dim, E, D, S1, S2, T>
+    dfdx_nn_core::Module<(
+        Tensor<(S1, M), E, D, T>,
+        Tensor<(S2, M), E, D>,
+        Tensor<(S2, M), E, D>,
+        AttentionMask<Const<1>, S1, S2, E, D>,
+    )> for MultiHeadAttention<M, H, K, V, E, D>
+where
+    E: Dtype + Float,
+    D: Device<E>,
+    S1: Dim,
+    S2: Dim,
+    T: Tape<E, D>,
+{
+    type Output = Tensor<(S1, M), E, D, T>;
+    type Error = D::Err;
+
+    /// Encoder-Decoder style self attention with masked logits
+    fn try_forward(
+        &self,
+        (q, k, v, mask): (
+            Tensor<(S1, M), E, D, T>,
+            Tensor<(S2, M), E, D>,
+            Tensor<(S2, M), E, D>,
+            AttentionMask<Const<1>, S1, S2, E, D>,
+        ),
+    ) -> Result<Self::Output, D::Err> {
+        assert_eq!(k.shape().0, v.shape().0);
+        let (s1, m) = *q.shape();
+        let s2 = k.shape().0;
+        let q = q.try_broadcast_like(&(Const::<1>, s1, m))?;
+        let k = k.try_broadcast_like(&(Const::<1>, s2, m))?;
+        let v = v.try_broadcast_like(&(Const::<1>, s2, m))?;
+        let out = self.try_attend(q, k, v, Some(&mask), false, None)?;
+        out.try_reshape_like(&(s1, m))
+    }
+
+    fn try_forward_mut(
+        &mut self,
+        (q, k, v, mask): (
+            Tensor<(S1, M), E, D, T>,
+            Tensor<(S2, M), E, D>,
+            Tensor<(S2, M), E, D>,
+            AttentionMask<Const<1>, S1, S2, E, D>,
+        ),
+    ) -> Result<Self::Output, D::Err> {
+        assert_eq!(k.shape().0, v.shape().0);
+        let (s1, m) = *q.shape();
+        let s2 = k.shape().0;
+        let q = q.try_broadcast_like(&(Const::<1>, s1, m))?;
+        let k = k.try_broadcast_like(&(Const::<1>, s2, m))?;
+        let v = v.try_broadcast_like(&(Const::<1>, s2, m))?;
+        let out = self.try_attend(q, k, v, Some(&mask), true, None)?;
+        out.try_reshape_like(&(s1, m))
+    }
 }
 
 impl<M: Dim, H: Dim, K: Dim, V: Dim, E, D, B, S1, S2, T>
@@ -98,32 +294,326 @@ where
             Tensor<(B, S2, M), E, D>,
         ),
     ) -> Result<Self::Output, D::Err> {
+        self.try_attend(q, k, v, None, false, None)
+    }
+
+    fn try_forward_mut(
+        &mut self,
+        (q, k, v): (
+            Tensor<(B, S1, M), E, D, T>,
+            Tensor<(B, S2, M), E, D>,
+            Tensor<(B, S2, M), E, D>,
+        ),
+    ) -> Result<Self::Output, D::Err> {
+        self.try_attend(q, k, v, None, true, None)
+    }
+}
+
+impl<M: Dim, H: Dim, K: Dim, V: Dim, E, D, B, S1, S2, T>
+    dfdx_nn_core::Module<(
+        Tensor<(B, S1, M), E, D, T>,
+        Tensor<(B, S2, M), E, D>,
+        Tensor<(B, S2, M), E, D>,
+        AttentionMask<B, S1, S2, E, D>,
+    )> for MultiHeadAttention<M, H, K, V, E, D>
+where
+    E: Dtype + Float,
+    D: Device<E>,
+    B: Dim,
+    S1: Dim,
+    S2: Dim,
+    T: Tape<E, D>,
+{
+    type Output = Tensor<(B, S1, M), E, D, T>;
+    type Error = D::Err;
+
+    /// Batched Encoder-Decoder style self attention with masked logits
+    fn try_forward(
+        &self,
+        (q, k, v, mask): (
+            Tensor<(B, S1, M), E, D, T>,
+            Tensor<(B, S2, M), E, D>,
+            Tensor<(B, S2, M), E, D>,
+            AttentionMask<B, S1, S2, E, D>,
+        ),
+    ) -> Result<Self::Output, D::Err> {
+        self.try_attend(q, k, v, Some(&mask), false, None)
+    }
+
+    fn try_forward_mut(
+        &mut self,
+        (q, k, v, mask): (
+            Tensor<(B, S1, M), E, D, T>,
+            Tensor<(B, S2, M), E, D>,
+            Tensor<(B, S2, M), E, D>,
+            AttentionMask<B, S1, S2, E, D>,
+        ),
+    ) -> Result<Self::Output, D::Err> {
+        self.try_attend(q, k, v, Some(&mask), true, None)
+    }
+}
+
+impl<M: Dim, H: Dim, K: Dim, V: Dim, E, D, S, T> dfdx_nn_core::Module<Tensor<(S, M), E, D, T>>
+    for MultiHeadAttention<M, H, K, V, E, D>
+where
+    E: Dtype + Float,
+    D: Device<E>,
+    S: Dim,
+    T: Tape<E, D>,
+{
+    type Output = Tensor<(S, M), E, D, T>;
+    type Error = D::Err;
+
+    /// Self attention
+    fn try_forward(&self, src: Tensor<(S, M), E, D, T>) -> Result<Self::Output, D::Err> {
+        let (s, m) = *src.shape();
+        let src = src.try_broadcast_like(&(Const::<1>, s, m))?;
+        let out = self.try_self_attend(src, None, false)?;
+        out.try_reshape_like(&(s, m))
+    }
+
+    fn try_forward_mut(&mut self, src: Tensor<(S, M), E, D, T>) -> Result<Self::Output, D::Err> {
+        let (s, m) = *src.shape();
+        let src = src.try_broadcast_like(&(Const::<1>, s, m))?;
+        let out = self.try_self_attend(src, None, true)?;
+        out.try_reshape_like(&(s, m))
+    }
+}
+
+impl<M: Dim, H: Dim, K: Dim, V: Dim, E, D, S, T>
+    dfdx_nn_core::Module<(Tensor<(S, M), E, D, T>, AttentionMask<Const<1>, S, S, E, D>)>
+    for MultiHeadAttention<M, H, K, V, E, D>
+where
+    E: Dtype + Float,
+    D: Device<E>,
+    S: Dim,
+    T: Tape<E, D>,
+{
+    type Output = Tensor<(S, M), E, D, T>;
+    type Error = D::Err;
+
+    /// Self attention with masked logits
+    fn try_forward(
+        &self,
+        (src, mask): (Tensor<(S, M), E, D, T>, AttentionMask<Const<1>, S, S, E, D>),
+    ) -> Result<Self::Output, D::Err> {
+        let (s, m) = *src.shape();
+        let src = src.try_broadcast_like(&(Const::<1>, s, m))?;
+        let out = self.try_self_attend(src, Some(&mask), false)?;
+        out.try_reshape_like(&(s, m))
+    }
+
+    fn try_forward_mut(
+        &mut self,
+        (src, mask): (Tensor<(S, M), E, D, T>, AttentionMask<Const<1>, S, S, E, D>),
+    ) -> Result<Self::Output, D::Err> {
+        let (s, m) = *src.shape();
+        let src = src.try_broadcast_like(&(Const::<1>, s, m))?;
+        let out = self.try_self_attend(src, Some(&mask), true)?;
+        out.try_reshape_like(&(s, m))
+    }
+}
+
+impl<M: Dim, H: Dim, K: Dim, V: Dim, E, D, B, S, T> dfdx_nn_core::Module<Tensor<(B, S, M), E, D, T>>
+    for MultiHeadAttention<M, H, K, V, E, D>
+where
+    E: Dtype + Float,
+    D: Device<E>,
+    B: Dim,
+    S: Dim,
+    T: Tape<E, D>,
+{
+    type Output = Tensor<(B, S, M), E, D, T>;
+    type Error = D::Err;
+
+    /// Batched self attention
+    fn try_forward(&self, src: Tensor<(B, S, M), E, D, T>) -> Result<Self::Output, D::Err> {
+        self.try_self_attend(src, None, false)
+    }
+
+    fn try_forward_mut(&mut self, src: Tensor<(B, S, M), E, D, T>) -> Result<Self::Output, D::Err> {
+        self.try_self_attend(src, None, true)
+    }
+}
+
+impl<M: Dim, H: Dim, K: Dim, V: Dim, E, D, B, S, T>
+    dfdx_nn_core::Module<(Tensor<(B, S, M), E, D, T>, AttentionMask<B, S, S, E, D>)>
+    for MultiHeadAttention<M, H, K, V, E, D>
+where
+    E: Dtype + Float,
+    D: Device<E>,
+    B: Dim,
+    S: Dim,
+    T: Tape<E, D>,
+{
+    type Output = Tensor<(B, S, M), E, D, T>;
+    type Error = D::Err;
+
+    /// Batched self attention with masked logits
+    fn try_forward(
+        &self,
+        (src, mask): (Tensor<(B, S, M), E, D, T>, AttentionMask<B, S, S, E, D>),
+    ) -> Result<Self::Output, D::Err> {
+        self.try_self_attend(src, Some(&mask), false)
+    }
+
+    fn try_forward_mut(
+        &mut self,
+        (src, mask): (Tensor<(B, S, M), E, D, T>, AttentionMask<B, S, S, E, D>),
+    ) -> Result<Self::Output, D::Err> {
+        self.try_self_attend(src, Some(&mask), true)
+    }
+}
+
+impl<M: Dim, H: Dim, K: Dim, V: Dim, E, D, B, S1>
+    dfdx_nn_core::Module<(Tensor<(B, S1, M), E, D>, Option<KVCache<B, K, V, E, D>>)>
+    for MultiHeadAttention<M, H, K, V, E, D>
+where
+    E: Dtype + Float,
+    D: Device<E>,
+    B: Dim,
+    S1: Dim,
+{
+    type Output = (Tensor<(B, S1, M), E, D>, KVCache<B, K, V, E, D>);
+    type Error = D::Err;
+
+    /// Batched self attention of new tokens, using & extending a [KVCache]
+    fn try_forward(
+        &self,
+        (x, cache): (Tensor<(B, S1, M), E, D>, Option<KVCache<B, K, V, E, D>>),
+    ) -> Result<Self::Output, D::Err> {
+        let (b, s1, _) = *x.shape();
+        let q_pos = cache.as_ref().map_or(0, |cache| cache.len());
+        let k = self.w_k.try_forward(x.clone())?;
+        let k = k.try_reshape_like(&(b, s1.size(), self.k_dim))?;
+        let v = self.w_v.try_forward(x.clone())?;
+        let v = v.try_reshape_like(&(b, s1.size(), self.v_dim))?;
+
+        let cache = match cache {
+            None => KVCache { keys: k, values: v },
+            Some(cache) => {
+                assert_eq!(cache.keys.shape().0.size(), b.size());
+                KVCache {
+                    keys: (cache.keys, k).try_concat_along(Axis::<1>)?,
+                    values: (cache.values, v).try_concat_along(Axis::<1>)?,
+                }
+            }
+        };
+
+        let keys = cache.keys.clone();
+        let values = cache.values.clone();
+        let y = self.try_attend_projected(x, keys, values, None, false, Some(q_pos))?;
+        Ok((y, cache))
+    }
+}
+
+impl<M: Dim, H: Dim, K: Dim, V: Dim, E: Dtype + Float, D: Device<E>>
+    MultiHeadAttention<M, H, K, V, E, D>
+{
+    /// Batched self attention, with RoPE applied if configured.
+    fn try_self_attend<B: Dim, S: Dim, T: Tape<E, D>>(
+        &self,
+        src: Tensor<(B, S, M), E, D, T>,
+        mask: Option<&AttentionMask<B, S, S, E, D>>,
+        train: bool,
+    ) -> Result<Tensor<(B, S, M), E, D, T>, D::Err> {
+        let (src, tape) = src.split_tape();
+        self.try_attend(
+            src.clone().put_tape(tape),
+            src.clone(),
+            src,
+            mask,
+            train,
+            Some(0),
+        )
+    }
+
+    /// Batched attention. `train` is set when called from [Module::try_forward_mut], so that
+    /// dropout is applied to the attention weights in training mode. `q_pos` is the position
+    /// of the first query among the keys, which is only known for self attention. RoPE is only
+    /// applied when it is set.
+    fn try_attend<B: Dim, S1: Dim, S2: Dim, T: Tape<E, D>>(
+        &self,
+        q: Tensor<(B, S1, M), E, D, T>,
+        k: Tensor<(B, S2, M), E, D>,
+        v: Tensor<(B, S2, M), E, D>,
+        mask: Option<&AttentionMask<B, S1, S2, E, D>>,
+        train: bool,
+        q_pos: Option<usize>,
+    ) -> Result<Tensor<(B, S1, M), E, D, T>, D::Err> {
         assert_eq!(q.shape().0, k.shape().0);
         assert_eq!(q.shape().0, v.shape().0);
         assert_eq!(k.shape().1, v.shape().1);
 
+        let v = self.w_v.try_forward(v.retaped::<T>())?;
+        let k = self.w_k.try_forward(k.retaped::<T>())?;
+        self.try_attend_projected(q, k, v, mask, train, q_pos)
+    }
+
+    /// Attention of `q` to keys & values that are already projected by `w_k` & `w_v`.
+    fn try_attend_projected<B: Dim, S1: Dim, S2: Dim, T: Tape<E, D>>(
+        &self,
+        q: Tensor<(B, S1, M), E, D, T>,
+        k: Tensor<(B, S2, K), E, D, T>,
+        v: Tensor<(B, S2, V), E, D, T>,
+        mask: Option<&AttentionMask<B, S1, S2, E, D>>,
+        train: bool,
+        q_pos: Option<usize>,
+    ) -> Result<Tensor<(B, S1, M), E, D, T>, D::Err> {
         let (b, s1, _) = *q.shape();
         let s2 = v.shape().1;
         let h_dim = self.num_heads.size();
         let k_dim = self.k_dim.size();
         let v_dim = self.v_dim.size();
 
-        let v = self.w_v.try_forward(v.retaped::<T>())?;
         let v = v.try_reshape_like(&(b, s2, h_dim, v_dim / h_dim))?;
         let v = v.try_permute::<_, Axes4<0, 2, 1, 3>>()?;
 
-        let k = self.w_k.try_forward(k.retaped::<T>())?;
         let k = k.try_reshape_like(&(b, s2, h_dim, k_dim / h_dim))?;
-        let k = k.try_permute::<_, Axes4<0, 2, 3, 1>>()?;
-
         let q = self.w_q.try_forward(q)?;
         let q = q.try_reshape_like(&(b, s1, h_dim, k_dim / h_dim))?;
+
+        let (q, k) = match (self.rope_theta, q_pos) {
+            (Some(theta), Some(q_pos)) => (try_rotate(q, q_pos, theta)?, try_rotate(k, 0, theta)?),
+            _ => (q, k),
+        };
+
+        let k = k.try_permute::<_, Axes4<0, 2, 3, 1>>()?;
         let q = q.try_permute::<_, Axes4<0, 2, 1, 3>>()?;
 
         // Get weights
         let scalar: E = E::from_f64(1.0 / ((k_dim / h_dim) as f64).sqrt()).unwrap();
-        let weights = q.try_matmul(k)?.try_mul(scalar)?;
+        let mut weights = q.try_matmul(k)?.try_mul(scalar)?;
+
+        // Mask out logits
+        let shape = *weights.shape();
+        if self.causal {
+            // query `i` is at position `s2 - s1 + i` of the keys
+            let diagonal = s2.size() as isize - s1.size() as isize + 1;
+            let causal = v
+                .device()
+                .try_upper_tri_like(&(s1, s2), E::neg_infinity(), diagonal)?;
+            weights = weights.try_add(causal.try_broadcast_like(&shape)?)?;
+        }
+        if let Some(attn) = mask.and_then(|m| m.attn.as_ref()) {
+            weights = weights.try_add(attn.clone().try_broadcast_like(&shape)?)?;
+        }
+        if let Some(padding) = mask.and_then(|m| m.key_padding.as_ref()) {
+            weights = weights.try_add(padding.clone().try_broadcast_like(&shape)?)?;
+        }
+        if self.causal || mask.is_some() {
+            // a query that can't attend to any key has a row of all -inf logits, whose softmax
+            // is NaN. raise them to the lowest finite value, so it attends uniformly instead.
+            let lowest = <E as Float>::min_value().to_f64().unwrap();
+            weights = weights.try_clamp(lowest, f64::INFINITY)?;
+        }
+
         let weights = weights.try_softmax::<Axis<3>>()?;
+        let weights = if train && self.dropout.training {
+            self.dropout.train_fwd(weights)?
+        } else {
+            weights
+        };
 
         // Get new tokens
         let tokens = weights.try_matmul(v)?;
@@ -134,19 +624,45 @@ where
     }
 }
 
-impl<M: Dim, H: Dim, K: Dim, V: Dim, E, D, Src> dfdx_nn_core::Module<Src>
-    for MultiHeadAttention<M, H, K, V, E, D>
-where
-    E: Dtype,
-    D: Device<E>,
-    Src: SplitTape,
-    Self: dfdx_nn_core::Module<(Src, Src::NoTape, Src::NoTape), Output = Src, Error = D::Err>,
-{
-    type Output = Src;
-    type Error = D::Err;
+/// Applies the rotary position embedding to `x` of shape `(batch, seq, heads, head_dim)`, where
+/// the first position of `x` is `offset`. Each pair of features `(j, j + head_dim / 2)` is
+/// rotated by the angle `pos * theta^(-2j / head_dim)`.
+fn try_rotate<B: Dim, S: Dim, E: Dtype + Float, D: Device<E>, T: Tape<E, D>>(
+    x: Tensor<(B, S, usize, usize), E, D, T>,
+    offset: usize,
+    theta: f64,
+) -> Result<Tensor<(B, S, usize, usize), E, D, T>, D::Err> {
+    let (b, s, h, d) = *x.shape();
+    assert_eq!(d % 2, 0, "rotary embedding requires an even head dimension");
+    let half = d / 2;
 
-    fn try_forward(&self, src: Src) -> Result<Self::Output, D::Err> {
-        let (src, tape) = src.split_tape();
-        self.try_forward((src.clone().put_tape(tape), src.clone(), src))
+    let mut cos = Vec::with_capacity(s.size() * half);
+    let mut sin = Vec::with_capacity(s.size() * half);
+    for pos in offset..offset + s.size() {
+        for j in 0..half {
+            let freq = theta.powf(-2.0 * j as f64 / d as f64);
+            let angle = pos as f64 * freq;
+            cos.push(E::from_f64(angle.cos()).unwrap());
+            sin.push(E::from_f64(angle.sin()).unwrap());
+        }
     }
+
+    let half_shape = (b, s, h, half);
+    let dev = x.device().clone();
+    let cos = dev
+        .try_tensor_from_vec(cos, (s, half))?
+        .try_broadcast_like::<_, Axes2<0, 2>>(&half_shape)?;
+    let sin = dev
+        .try_tensor_from_vec(sin, (s, half))?
+        .try_broadcast_like::<_, Axes2<0, 2>>(&half_shape)?;
+
+    // `[x1 * cos - x2 * sin, x2 * cos + x1 * sin]` on the halves `x1` & `x2`
+    let x1 = x.retaped::<T>().try_slice((.., .., .., ..half))?;
+    let x2 = x.try_slice((.., .., .., half..))?;
+    let y1 = x1
+        .retaped::<T>()
+        .try_mul(cos.clone())?
+        .try_sub(x2.retaped::<T>().try_mul(sin.clone())?)?;
+    let y2 = x2.try_mul(cos)?.try_add(x1.try_mul(sin)?)?;
+    (y1, y2).try_concat_along(Axis::<3>)
 }
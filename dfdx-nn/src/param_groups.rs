@@ -0,0 +1,135 @@
+use dfdx::tensor_ops::{AdamConfig, Momentum, RMSpropConfig, SgdConfig, WeightDecay};
+
+/// Selects parameters by their full dotted name, e.g. `l1.matmul.weight`.
+#[derive(Debug, Clone)]
+pub enum ParamFilter {
+    /// Names starting with this, e.g. `"backbone."`.
+    Prefix(String),
+    /// Names for which this returns `true`.
+    Predicate(fn(&str) -> bool),
+}
+
+impl ParamFilter {
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            Self::Prefix(prefix) => name.starts_with(prefix.as_str()),
+            Self::Predicate(f) => f(name),
+        }
+    }
+}
+
+/// Overrides part of an optimizer's config for the parameters selected by `filter`.
+///
+/// Each parameter uses the first group that matches it, or the optimizer's config
+/// if there is none. E.g. no weight decay on norms and biases, and a lower learning rate
+/// for a pretrained backbone:
+/// ```ignore
+/// opt.param_groups = vec![
+///     ParamGroup {
+///         weight_decay: Some(0.0),
+///         ..ParamGroup::predicate(|n| n.ends_with("gamma") || n.ends_with("beta") || n.ends_with("bias"))
+///     },
+///     ParamGroup {
+///         lr_scale: 0.1,
+///         ..ParamGroup::prefix("backbone.")
+///     },
+/// ];
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParamGroup {
+    pub filter: ParamFilter,
+    /// Multiplies the optimizer's learning rate, so that the group still follows
+    /// [crate::LrScheduler]s.
+    pub lr_scale: f64,
+    /// Replaces the amount of weight decay, keeping the kind of decay the optimizer uses.
+    /// `Some(0.0)` disables weight decay for the group.
+    pub weight_decay: Option<f64>,
+    /// Replaces the momentum (`betas[0]` for [crate::Adam]).
+    pub momentum: Option<f64>,
+}
+
+impl ParamGroup {
+    /// A group of all parameters whose name starts with `prefix`, with no overrides.
+    pub fn prefix(prefix: impl Into<String>) -> Self {
+        Self::new(ParamFilter::Prefix(prefix.into()))
+    }
+
+    /// A group of all parameters whose name matches `f`, with no overrides.
+    pub fn predicate(f: fn(&str) -> bool) -> Self {
+        Self::new(ParamFilter::Predicate(f))
+    }
+
+    fn new(filter: ParamFilter) -> Self {
+        Self {
+            filter,
+            lr_scale: 1.0,
+            weight_decay: None,
+            momentum: None,
+        }
+    }
+}
+
+pub(crate) trait ApplyParamGroup: Copy {
+    fn apply(self, group: &ParamGroup) -> Self;
+}
+
+/// `cfg` with the overrides of the first group matching `name` applied.
+pub(crate) fn group_cfg<Cfg: ApplyParamGroup>(groups: &[ParamGroup], name: &str, cfg: Cfg) -> Cfg {
+    match groups.iter().find(|g| g.filter.matches(name)) {
+        Some(group) => cfg.apply(group),
+        None => cfg,
+    }
+}
+
+fn weight_decay(base: Option<WeightDecay>, amount: f64) -> Option<WeightDecay> {
+    if amount == 0.0 {
+        return None;
+    }
+    match base {
+        Some(WeightDecay::Decoupled(_)) => Some(WeightDecay::Decoupled(amount)),
+        _ => Some(WeightDecay::L2(amount)),
+    }
+}
+
+impl ApplyParamGroup for SgdConfig {
+    fn apply(mut self, group: &ParamGroup) -> Self {
+        self.lr *= group.lr_scale;
+        if let Some(wd) = group.weight_decay {
+            self.weight_decay = weight_decay(self.weight_decay, wd);
+        }
+        if let Some(m) = group.momentum {
+            self.momentum = match self.momentum {
+                _ if m == 0.0 => None,
+                Some(Momentum::Nesterov(_)) => Some(Momentum::Nesterov(m)),
+                _ => Some(Momentum::Classic(m)),
+            };
+        }
+        self
+    }
+}
+
+impl ApplyParamGroup for AdamConfig {
+    fn apply(mut self, group: &ParamGroup) -> Self {
+        self.lr *= group.lr_scale;
+        if let Some(wd) = group.weight_decay {
+            self.weight_decay = weight_decay(self.weight_decay, wd);
+        }
+        if let Some(m) = group.momentum {
+            self.betas[0] = m;
+        }
+        self
+    }
+}
+
+impl ApplyParamGroup for RMSpropConfig {
+    fn apply(mut self, group: &ParamGroup) -> Self {
+        self.lr *= group.lr_scale;
+        if let Some(wd) = group.weight_decay {
+            self.weight_decay = weight_decay(self.weight_decay, wd);
+        }
+        if let Some(m) = group.momentum {
+            self.momentum = if m == 0.0 { None } else { Some(m) };
+        }
+        self
+    }
+}
@@ -0,0 +1,219 @@
+use crate::*;
+use dfdx::prelude::*;
+use rand_distr::{Distribution, StandardNormal};
+
+#[derive(Default, Clone, Copy, Debug)]
+pub struct SinusoidalPositionalEncodingConfig<MaxLen: Dim, Model: Dim> {
+    pub max_len: MaxLen,
+    pub model: Model,
+}
+
+pub type SinusoidalPositionalEncodingConstConfig<const L: usize, const M: usize> =
+    SinusoidalPositionalEncodingConfig<Const<L>, Const<M>>;
+
+impl<L: Dim, M: Dim, E: Dtype, D: Device<E>> BuildOnDevice<E, D>
+    for SinusoidalPositionalEncodingConfig<L, M>
+{
+    type Built = SinusoidalPositionalEncoding<L, M, E, D>;
+    fn try_build_on_device(&self, device: &D) -> Result<Self::Built, D::Err> {
+        let (l, m) = (self.max_len.size(), self.model.size());
+        let mut pe = Vec::with_capacity(l * m);
+        for pos in 0..l {
+            for i in 0..m {
+                let freq = 10000f64.powf(-((i - i % 2) as f64) / m as f64);
+                let angle = pos as f64 * freq;
+                let x = if i % 2 == 0 { angle.sin() } else { angle.cos() };
+                pe.push(E::from_f64(x).unwrap());
+            }
+        }
+        Ok(SinusoidalPositionalEncoding {
+            encodings: device.try_tensor_from_vec(pe, (self.max_len, self.model))?,
+        })
+    }
+}
+
+/// Adds the fixed sinusoidal position encodings of "Attention Is All You Need" to a sequence
+/// of embeddings:
+/// `pe[pos, 2i] = sin(pos / 10000^(2i / M))` and `pe[pos, 2i + 1] = cos(pos / 10000^(2i / M))`.
+///
+/// The encodings of the first `L` positions are computed once when built, so sequences can be
+/// at most `L` long. They aren't trained or saved.
+///
+/// Like [LearnedPositionalEmbedding], pass `(x, offset)` for a sequence that starts at position
+/// `offset`.
+#[derive(
+    Clone, Debug, ResetParams, VisitTensors, SaveSafeTensors, LoadSafeTensors, SetTraining,
+)]
+pub struct SinusoidalPositionalEncoding<L: Dim, M: Dim, Elem: Dtype, Dev: Device<Elem>> {
+    pub encodings: Tensor<(L, M), Elem, Dev>,
+}
+
+impl<L: Dim, M: Dim, S: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>> Module<Tensor<(S, M), E, D, T>>
+    for SinusoidalPositionalEncoding<L, M, E, D>
+{
+    type Output = Tensor<(S, M), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<(S, M), E, D, T>) -> Result<Self::Output, D::Err> {
+        self.try_forward((x, 0))
+    }
+}
+
+impl<L: Dim, M: Dim, S: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<(Tensor<(S, M), E, D, T>, usize)> for SinusoidalPositionalEncoding<L, M, E, D>
+{
+    type Output = Tensor<(S, M), E, D, T>;
+    type Error = D::Err;
+
+    /// Forward of a sequence whose first token is at position `offset`, e.g. the new tokens
+    /// of incremental decoding with a [KVCache].
+    fn try_forward(
+        &self,
+        (x, offset): (Tensor<(S, M), E, D, T>, usize),
+    ) -> Result<Self::Output, D::Err> {
+        let pe = try_positions(self.encodings.clone(), offset, x.shape().0)?;
+        x.try_add(pe)
+    }
+}
+
+impl<L: Dim, M: Dim, B: Dim, S: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<Tensor<(B, S, M), E, D, T>> for SinusoidalPositionalEncoding<L, M, E, D>
+{
+    type Output = Tensor<(B, S, M), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<(B, S, M), E, D, T>) -> Result<Self::Output, D::Err> {
+        self.try_forward((x, 0))
+    }
+}
+
+impl<L: Dim, M: Dim, B: Dim, S: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<(Tensor<(B, S, M), E, D, T>, usize)> for SinusoidalPositionalEncoding<L, M, E, D>
+{
+    type Output = Tensor<(B, S, M), E, D, T>;
+    type Error = D::Err;
+
+    /// Batched forward of sequences whose first token is at position `offset`
+    fn try_forward(
+        &self,
+        (x, offset): (Tensor<(B, S, M), E, D, T>, usize),
+    ) -> Result<Self::Output, D::Err> {
+        let shape = *x.shape();
+        let pe = try_positions(self.encodings.clone(), offset, shape.1)?;
+        x.try_add(pe.try_broadcast_like(&shape)?)
+    }
+}
+
+/// Rows `offset..offset + s` of `table`, i.e. the encodings of a sequence of length `s` whose
+/// first token is at position `offset`.
+fn try_positions<L: Dim, M: Dim, S: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>(
+    table: Tensor<(L, M), E, D, T>,
+    offset: usize,
+    s: S,
+) -> Result<Tensor<(S, M), E, D, T>, D::Err> {
+    let (l, m) = *table.shape();
+    assert!(
+        offset + s.size() <= l.size(),
+        "Sequence of length {} at position {} is longer than the max length {}",
+        s.size(),
+        offset,
+        l.size()
+    );
+    table
+        .try_slice((offset..offset + s.size(), ..))?
+        .try_reshape_like(&(s, m))
+}
+
+#[derive(Default, Clone, Copy, Debug)]
+pub struct LearnedPositionalEmbeddingConfig<MaxLen: Dim, Model: Dim> {
+    pub max_len: MaxLen,
+    pub model: Model,
+}
+
+pub type LearnedPositionalEmbeddingConstConfig<const L: usize, const M: usize> =
+    LearnedPositionalEmbeddingConfig<Const<L>, Const<M>>;
+
+impl<L: Dim, M: Dim, E: Dtype, D: Device<E>> BuildOnDevice<E, D>
+    for LearnedPositionalEmbeddingConfig<L, M>
+{
+    type Built = LearnedPositionalEmbedding<L, M, E, D>;
+    fn try_build_on_device(&self, device: &D) -> Result<Self::Built, D::Err> {
+        Ok(LearnedPositionalEmbedding {
+            weight: device.try_zeros_like(&(self.max_len, self.model))?,
+        })
+    }
+}
+
+/// Adds a learned embedding of each position to a sequence of embeddings. Sequences can be at
+/// most `L` long.
+///
+/// Pass `(x, offset)` for a sequence that starts at position `offset`, e.g. when decoding one
+/// token at a time with a [KVCache].
+#[derive(Clone, Debug, VisitTensors, SaveSafeTensors, LoadSafeTensors, SetTraining)]
+pub struct LearnedPositionalEmbedding<L: Dim, M: Dim, Elem: Dtype, Dev: Device<Elem>> {
+    #[param]
+    #[serialize]
+    pub weight: Tensor<(L, M), Elem, Dev>,
+}
+
+impl<L: Dim, M: Dim, E: Dtype, D: Device<E>> ResetParams<E, D>
+    for LearnedPositionalEmbedding<L, M, E, D>
+where
+    StandardNormal: Distribution<E>,
+{
+    fn try_reset_params(&mut self) -> Result<(), D::Err> {
+        self.weight.try_fill_with_distr(StandardNormal)
+    }
+}
+
+impl<L: Dim, M: Dim, S: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>> Module<Tensor<(S, M), E, D, T>>
+    for LearnedPositionalEmbedding<L, M, E, D>
+{
+    type Output = Tensor<(S, M), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<(S, M), E, D, T>) -> Result<Self::Output, D::Err> {
+        self.try_forward((x, 0))
+    }
+}
+
+impl<L: Dim, M: Dim, S: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<(Tensor<(S, M), E, D, T>, usize)> for LearnedPositionalEmbedding<L, M, E, D>
+{
+    type Output = Tensor<(S, M), E, D, T>;
+    type Error = D::Err;
+
+    /// Forward of a sequence whose first token is at position `offset`, e.g. the new tokens
+    /// of incremental decoding with a [KVCache].
+    fn try_forward(
+        &self,
+        (x, offset): (Tensor<(S, M), E, D, T>, usize),
+    ) -> Result<Self::Output, D::Err> {
+        let pe = try_positions(self.weight.retaped::<T>(), offset, x.shape().0)?;
+        x.try_add(pe)
+    }
+}
+
+impl<L: Dim, M: Dim, B: Dim, S: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<Tensor<(B, S, M), E, D, T>> for LearnedPositionalEmbedding<L, M, E, D>
+{
+    type Output = Tensor<(B, S, M), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<(B, S, M), E, D, T>) -> Result<Self::Output, D::Err> {
+        self.try_forward((x, 0))
+    }
+}
+
+impl<L: Dim, M: Dim, B: Dim, S: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<(Tensor<(B, S, M), E, D, T>, usize)> for LearnedPositionalEmbedding<L, M, E, D>
+{
+    type Output = Tensor<(B, S, M), E, D, T>;
+    type Error = D::Err;
+
+    /// Batched forward of sequences whose first token is at position `offset`
+    fn try_forward(
+        &self,
+        (x, offset): (Tensor<(B, S, M), E, D, T>, usize),
+    ) -> Result<Self::Output, D::Err> {
+        let shape = *x.shape();
+        let pe = try_positions(self.weight.retaped::<T>(), offset, shape.1)?;
+        x.try_add(pe.try_broadcast_like(&shape)?)
+    }
+}
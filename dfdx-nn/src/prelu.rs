@@ -0,0 +1,40 @@
+use crate::*;
+use dfdx::prelude::*;
+
+#[derive(Default, Clone, Copy, Debug)]
+pub struct PReLUConfig;
+
+impl<E: Dtype, D: Device<E>> BuildOnDevice<E, D> for PReLUConfig {
+    type Built = PReLU<E, D>;
+    fn try_build_on_device(&self, device: &D) -> Result<Self::Built, D::Err> {
+        Ok(PReLU {
+            weight: device.try_zeros_like(&())?,
+        })
+    }
+}
+
+/// [LeakyReLU] with a learned slope, `max(x, 0) + weight * min(x, 0)`. The slope starts at `0.25`.
+#[derive(Clone, Debug, VisitTensors, SaveSafeTensors, LoadSafeTensors, SetTraining)]
+pub struct PReLU<Elem: Dtype, Dev: Device<Elem>> {
+    #[param]
+    #[serialize]
+    pub weight: Tensor<(), Elem, Dev>,
+}
+
+impl<E: Dtype, D: Device<E>> ResetParams<E, D> for PReLU<E, D> {
+    fn try_reset_params(&mut self) -> Result<(), D::Err> {
+        self.weight.copy_from(&[E::from_f64(0.25).unwrap()]);
+        Ok(())
+    }
+}
+
+impl<S: Shape, E: Dtype, D: Device<E>, T: Tape<E, D>> Module<Tensor<S, E, D, T>> for PReLU<E, D> {
+    type Output = Tensor<S, E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<S, E, D, T>) -> Result<Self::Output, Self::Error> {
+        let shape = *x.shape();
+        let slope = self.weight.retaped::<T>().try_broadcast_like(&shape)?;
+        let neg = x.retaped::<T>().try_negate()?.try_relu()?.try_mul(slope)?;
+        x.try_relu()?.try_sub(neg)
+    }
+}
@@ -8,7 +8,7 @@ use dfdx::{
 use crate::Module;
 
 #[derive(
-    Default, Clone, Debug, ResetParams, ZeroGrads, UpdateParams, SaveSafeTensors, LoadSafeTensors,
+    Default, Clone, Debug, ResetParams, VisitTensors, SaveSafeTensors, LoadSafeTensors, SetTraining,
 )]
 #[repr(transparent)]
 pub struct ResidualAdd<T>(
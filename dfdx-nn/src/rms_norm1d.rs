@@ -0,0 +1,99 @@
+use crate::*;
+use dfdx::prelude::*;
+
+#[derive(Default, Clone, Copy, Debug)]
+#[repr(transparent)]
+pub struct RMSNorm1DConfig<M: Dim>(pub M);
+
+pub type RMSNorm1DConstConfig<const M: usize> = RMSNorm1DConfig<Const<M>>;
+
+impl<M: Dim, E: Dtype, D: Device<E>> crate::BuildOnDevice<E, D> for RMSNorm1DConfig<M> {
+    type Built = RMSNorm1D<M, E, D>;
+    fn try_build_on_device(&self, device: &D) -> Result<Self::Built, D::Err> {
+        Ok(RMSNorm1D {
+            gamma: device.try_ones_like(&(self.0,))?,
+            epsilon: 1e-5,
+        })
+    }
+}
+
+/// Root mean square normalization over the last axis, followed by a learnable gain.
+///
+/// Unlike [LayerNorm1D] this doesn't center the input, and has no bias.
+#[derive(Clone, Debug, VisitTensors, SaveSafeTensors, LoadSafeTensors, SetTraining)]
+pub struct RMSNorm1D<M: Dim, Elem: Dtype, Dev: Device<Elem>> {
+    #[param]
+    #[serialize]
+    pub gamma: Tensor<(M,), Elem, Dev>,
+    #[serialize]
+    pub epsilon: f64,
+}
+
+impl<M: Dim, E: Dtype, D: Device<E>> crate::ResetParams<E, D> for RMSNorm1D<M, E, D> {
+    fn try_reset_params(&mut self) -> Result<(), D::Err> {
+        self.gamma.try_fill_with_ones()
+    }
+}
+
+impl<M: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>> crate::Module<Tensor<(M,), E, D, T>>
+    for RMSNorm1D<M, E, D>
+{
+    type Output = Tensor<(M,), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<(M,), E, D, T>) -> Result<Self::Output, Self::Error> {
+        let shape = *x.shape();
+        let rms = x
+            .retaped::<T>()
+            .try_square()?
+            .try_mean::<(), _>()?
+            .try_add(E::from_f64(self.epsilon).unwrap())?
+            .try_sqrt()?;
+        let x = x.try_div(rms.try_broadcast_like(&shape)?)?;
+        x.try_mul(self.gamma.retaped::<T>())
+    }
+}
+
+impl<Batch: Dim, M: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    crate::Module<Tensor<(Batch, M), E, D, T>> for RMSNorm1D<M, E, D>
+{
+    type Output = Tensor<(Batch, M), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<(Batch, M), E, D, T>) -> Result<Self::Output, Self::Error> {
+        let shape = *x.shape();
+        let rms = x
+            .retaped::<T>()
+            .try_square()?
+            .try_mean::<_, Axis<1>>()?
+            .try_add(E::from_f64(self.epsilon).unwrap())?
+            .try_sqrt()?;
+        let x = x.try_div(rms.try_broadcast_like(&shape)?)?;
+        self.gamma
+            .retaped::<T>()
+            .try_broadcast_like(&shape)?
+            .try_mul(x)
+    }
+}
+
+impl<Batch: Dim, Seq: Dim, M: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    crate::Module<Tensor<(Batch, Seq, M), E, D, T>> for RMSNorm1D<M, E, D>
+{
+    type Output = Tensor<(Batch, Seq, M), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(
+        &self,
+        x: Tensor<(Batch, Seq, M), E, D, T>,
+    ) -> Result<Self::Output, Self::Error> {
+        let shape = *x.shape();
+        let rms = x
+            .retaped::<T>()
+            .try_square()?
+            .try_mean::<_, Axis<2>>()?
+            .try_add(E::from_f64(self.epsilon).unwrap())?
+            .try_sqrt()?;
+        let x = x.try_div(rms.try_broadcast_like(&shape)?)?;
+        self.gamma
+            .retaped::<T>()
+            .try_broadcast_like(&shape)?
+            .try_mul(x)
+    }
+}
@@ -0,0 +1,124 @@
+use dfdx::{
+    shapes::{Dtype, HasShape, Shape},
+    tensor::{Gradients, Storage, Tensor, Tensorlike, UniqueId},
+    tensor_ops::{Device, RMSpropConfig},
+};
+
+use crate::{
+    param_groups::{group_cfg, ParamGroup},
+    OptimizerUpdateError, UpdateParams,
+};
+
+#[derive(Debug, Clone)]
+pub struct RMSprop<M, E: Dtype, D: Storage<E>> {
+    pub cfg: RMSpropConfig,
+    pub param_groups: Vec<ParamGroup>,
+    step: usize,
+    square_avg: Gradients<E, D>,
+    /// Only allocated when `cfg.momentum` is set.
+    momentums: Gradients<E, D>,
+    /// Only allocated when `cfg.centered` is set.
+    grad_avg: Gradients<E, D>,
+    /// Stand ins for `momentums` & `grad_avg` in the variants that don't use them. The kernel
+    /// never reads them, so each is one buffer as long as the largest parameter, shared by all
+    /// parameters.
+    scratch_m: Option<(usize, D::Vec)>,
+    scratch_ga: Option<(usize, D::Vec)>,
+    module: std::marker::PhantomData<*const M>,
+}
+
+impl<M, E: Dtype, D: Storage<E>> RMSprop<M, E, D> {
+    pub fn new(_model: &M, cfg: RMSpropConfig) -> Self {
+        Self {
+            cfg,
+            param_groups: Vec::new(),
+            step: 0,
+            square_avg: Gradients::leaky(),
+            momentums: Gradients::leaky(),
+            grad_avg: Gradients::leaky(),
+            scratch_m: None,
+            scratch_ga: None,
+            module: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<M, E: Dtype, D: Storage<E>> crate::HasLearningRate for RMSprop<M, E, D> {
+    fn learning_rate(&self) -> f64 {
+        self.cfg.lr
+    }
+    fn learning_rate_mut(&mut self) -> &mut f64 {
+        &mut self.cfg.lr
+    }
+}
+
+impl<M, E: Dtype, D: Device<E>> crate::Optimizer<M, E, D> for RMSprop<M, E, D> {
+    fn update_tensor<S: Shape>(
+        &mut self,
+        name: &str,
+        t: &mut Tensor<S, E, D>,
+        gradients: &Gradients<E, D>,
+        missing_params: &mut Vec<UniqueId>,
+    ) -> Result<(), D::Err> {
+        let g = gradients.get_ref_checked(t);
+        match g {
+            None => missing_params.push(t.id()),
+            Some(g) => {
+                let cfg = group_cfg(&self.param_groups, name, self.cfg);
+                let sa = self.square_avg.get_or_alloc_mut(t)?;
+
+                // initialize square_avg at step 0
+                if self.step == 0 {
+                    t.device().try_fill_with_ones(sa)?;
+                }
+
+                // momentum & grad_avg are only kept for the variants that use them
+                let numel = t.shape().num_elements();
+                let m = match cfg.momentum {
+                    Some(_) => self.momentums.get_or_alloc_mut(t)?,
+                    None => try_scratch(&mut self.scratch_m, t.device(), numel)?,
+                };
+                let ga = if cfg.centered {
+                    self.grad_avg.get_or_alloc_mut(t)?
+                } else {
+                    try_scratch(&mut self.scratch_ga, t.device(), numel)?
+                };
+
+                cfg.try_update(t, m, sa, ga, g)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        module: &mut M,
+        gradients: &Gradients<E, D>,
+    ) -> Result<(), OptimizerUpdateError<D::Err>>
+    where
+        M: UpdateParams<E, D>,
+    {
+        let mut missing_tensors = Vec::new();
+        module
+            .try_update_params(self, gradients, &mut missing_tensors)
+            .map_err(OptimizerUpdateError::DeviceError)?;
+        self.step += 1;
+        if missing_tensors.is_empty() {
+            Ok(())
+        } else {
+            Err(OptimizerUpdateError::UnusedTensors(missing_tensors))
+        }
+    }
+}
+
+/// `scratch` if it's at least `len` long, otherwise a new buffer of `len` that replaces it.
+fn try_scratch<'a, E: Dtype, D: Device<E>>(
+    scratch: &'a mut Option<(usize, D::Vec)>,
+    device: &D,
+    len: usize,
+) -> Result<&'a mut D::Vec, D::Err> {
+    if !matches!(scratch, Some((cap, _)) if *cap >= len) {
+        *scratch = Some((len, device.try_alloc_len(len)?));
+    }
+    Ok(&mut scratch.as_mut().unwrap().1)
+}
@@ -0,0 +1,130 @@
+use crate::*;
+use dfdx::prelude::*;
+
+/// Pools a sequence into the embedding of its first token, like the `[CLS]` token of BERT.
+/// `(S, M)` becomes `(M,)`, and `(B, S, M)` becomes `(B, M)`.
+///
+/// A BERT-style pooler is `(ClsPool, LinearConfig<M, M>, Tanh)`. Also accepts the sequence
+/// along with an [AttentionMask], which is ignored.
+#[derive(Default, Debug, Clone, Copy, CustomModule)]
+pub struct ClsPool;
+
+impl<S: Dim, M: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>> Module<Tensor<(S, M), E, D, T>>
+    for ClsPool
+{
+    type Output = Tensor<(M,), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<(S, M), E, D, T>) -> Result<Self::Output, D::Err> {
+        let m = x.shape().1;
+        x.try_slice((..1, ..))?.try_reshape_like(&(m,))
+    }
+}
+
+impl<B: Dim, S: Dim, M: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<Tensor<(B, S, M), E, D, T>> for ClsPool
+{
+    type Output = Tensor<(B, M), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<(B, S, M), E, D, T>) -> Result<Self::Output, D::Err> {
+        let (b, _, m) = *x.shape();
+        x.try_slice((.., ..1, ..))?.try_reshape_like(&(b, m))
+    }
+}
+
+impl<S: Dim, M: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<(Tensor<(S, M), E, D, T>, AttentionMask<Const<1>, S, S, E, D>)> for ClsPool
+{
+    type Output = Tensor<(M,), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(
+        &self,
+        (x, _): (Tensor<(S, M), E, D, T>, AttentionMask<Const<1>, S, S, E, D>),
+    ) -> Result<Self::Output, D::Err> {
+        self.try_forward(x)
+    }
+}
+
+impl<B: Dim, S: Dim, M: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<(Tensor<(B, S, M), E, D, T>, AttentionMask<B, S, S, E, D>)> for ClsPool
+{
+    type Output = Tensor<(B, M), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(
+        &self,
+        (x, _): (Tensor<(B, S, M), E, D, T>, AttentionMask<B, S, S, E, D>),
+    ) -> Result<Self::Output, D::Err> {
+        self.try_forward(x)
+    }
+}
+
+/// Pools a sequence into the mean of its token embeddings. `(S, M)` becomes `(M,)`, and
+/// `(B, S, M)` becomes `(B, M)`.
+///
+/// When given the sequence along with an [AttentionMask], the mean is only over the tokens that
+/// aren't key padding. Otherwise padding tokens are included in the mean. A sequence that is
+/// all padding pools to zeros.
+#[derive(Default, Debug, Clone, Copy, CustomModule)]
+pub struct MeanPool;
+
+impl<S: Dim, M: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>> Module<Tensor<(S, M), E, D, T>>
+    for MeanPool
+{
+    type Output = Tensor<(M,), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<(S, M), E, D, T>) -> Result<Self::Output, D::Err> {
+        x.try_mean::<(M,), Axis<0>>()
+    }
+}
+
+impl<B: Dim, S: Dim, M: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<Tensor<(B, S, M), E, D, T>> for MeanPool
+{
+    type Output = Tensor<(B, M), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(&self, x: Tensor<(B, S, M), E, D, T>) -> Result<Self::Output, D::Err> {
+        x.try_mean::<(B, M), Axis<1>>()
+    }
+}
+
+impl<S: Dim, M: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<(Tensor<(S, M), E, D, T>, AttentionMask<Const<1>, S, S, E, D>)> for MeanPool
+{
+    type Output = Tensor<(M,), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(
+        &self,
+        (x, mask): (Tensor<(S, M), E, D, T>, AttentionMask<Const<1>, S, S, E, D>),
+    ) -> Result<Self::Output, D::Err> {
+        let (s, m) = *x.shape();
+        let x = x.try_reshape_like(&(Const::<1>, s, m))?;
+        self.try_forward((x, mask))?.try_reshape_like(&(m,))
+    }
+}
+
+impl<B: Dim, S: Dim, M: Dim, E: Dtype, D: Device<E>, T: Tape<E, D>>
+    Module<(Tensor<(B, S, M), E, D, T>, AttentionMask<B, S, S, E, D>)> for MeanPool
+{
+    type Output = Tensor<(B, M), E, D, T>;
+    type Error = D::Err;
+    fn try_forward(
+        &self,
+        (x, mask): (Tensor<(B, S, M), E, D, T>, AttentionMask<B, S, S, E, D>),
+    ) -> Result<Self::Output, D::Err> {
+        let padding = match mask.key_padding {
+            Some(padding) => padding,
+            None => return self.try_forward(x),
+        };
+        let shape = *x.shape();
+        // the additive mask is 0 for kept tokens and -inf for padding, so this is 1 or 0
+        let keep = padding.try_exp()?;
+        // rows that are all padding have a sum of 0, so clamping their count gives 0 instead of NaN
+        let count = keep
+            .clone()
+            .try_sum::<(B,), Axis<1>>()?
+            .try_clamp(1.0, f64::INFINITY)?;
+        let sum = x
+            .try_mul(keep.try_broadcast_like(&shape)?)?
+            .try_sum::<(B, M), Axis<1>>()?;
+        sum.try_div(count.try_broadcast_like(&(shape.0, shape.2))?)
+    }
+}
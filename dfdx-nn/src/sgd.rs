@@ -4,9 +4,12 @@ use dfdx::{
     tensor_ops::{Device, SgdConfig},
 };
 
+use crate::param_groups::{group_cfg, ParamGroup};
+
 #[derive(Debug, Clone)]
 pub struct Sgd<M, E: Dtype, D: Storage<E>> {
     pub cfg: SgdConfig,
+    pub param_groups: Vec<ParamGroup>,
     velocity: Gradients<E, D>,
     module: std::marker::PhantomData<*const M>,
 }
@@ -15,15 +18,26 @@ impl<M, E: Dtype, D: Storage<E>> Sgd<M, E, D> {
     pub fn new(_model: &M, cfg: SgdConfig) -> Self {
         Self {
             cfg,
+            param_groups: Vec::new(),
             velocity: Gradients::leaky(),
             module: std::marker::PhantomData,
         }
     }
 }
 
+impl<M, E: Dtype, D: Storage<E>> crate::HasLearningRate for Sgd<M, E, D> {
+    fn learning_rate(&self) -> f64 {
+        self.cfg.lr
+    }
+    fn learning_rate_mut(&mut self) -> &mut f64 {
+        &mut self.cfg.lr
+    }
+}
+
 impl<M, E: Dtype, D: Device<E>> crate::Optimizer<M, E, D> for Sgd<M, E, D> {
     fn update_tensor<S: Shape>(
         &mut self,
+        name: &str,
         t: &mut Tensor<S, E, D>,
         gradients: &Gradients<E, D>,
         missing_params: &mut Vec<UniqueId>,
@@ -32,8 +46,9 @@ impl<M, E: Dtype, D: Device<E>> crate::Optimizer<M, E, D> for Sgd<M, E, D> {
         match g {
             None => missing_params.push(t.id()),
             Some(g) => {
+                let cfg = group_cfg(&self.param_groups, name, self.cfg);
                 let v = self.velocity.get_or_alloc_mut(t)?;
-                self.cfg.try_update(t, v, g)?;
+                cfg.try_update(t, v, g)?;
             }
         }
         Ok(())
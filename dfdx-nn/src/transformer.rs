@@ -3,8 +3,8 @@ use crate::*;
 use dfdx::{
     dtypes::Dtype,
     shapes::Dim,
-    tensor::{HasErr, PutTape, SplitTape, WithEmptyTape},
-    tensor_ops::{Device, TryAdd},
+    tensor::{HasErr, PutTape, SplitTape, Tensor, WithEmptyTape},
+    tensor_ops::{Device, TryAdd, TryMul},
 };
 
 #[derive(Clone, Debug, Sequential)]
@@ -12,16 +12,174 @@ use dfdx::{
 pub struct FeedForwardConfig<Model: Dim, F: Dim> {
     pub l1: LinearConfig<Model, F>,
     pub act1: ReLU,
+    pub drop1: Dropout,
     pub l2: LinearConfig<F, Model>,
+    pub drop2: Dropout,
 }
 
-#[derive(Clone, Debug, Sequential)]
+impl<Model: Dim, F: Dim> FeedForwardConfig<Model, F> {
+    pub fn new(model: Model, f: F) -> Self {
+        FeedForwardConfig {
+            l1: LinearConfig::new(model, f),
+            act1: ReLU,
+            drop1: Default::default(),
+            l2: LinearConfig::new(f, model),
+            drop2: Default::default(),
+        }
+    }
+}
+
+/// Feed forward configs whose dropout can be set by [EncoderBlockConfig::with_dropout] &
+/// [DecoderBlockConfig::with_dropout].
+pub trait WithDropout {
+    /// Sets the probability of all dropout layers.
+    fn with_dropout(self, p: f64) -> Self;
+}
+
+impl<Model: Dim, F: Dim> WithDropout for FeedForwardConfig<Model, F> {
+    fn with_dropout(mut self, p: f64) -> Self {
+        self.drop1.p = p;
+        self.drop2.p = p;
+        self
+    }
+}
+
+/// A gated feed forward, `down(act(gate(x)) * up(x))`. The projections have no bias, like in
+/// LLaMA & PaLM.
+///
+/// Use it in transformer blocks with e.g. [EncoderBlockConfig::with_ff].
+#[derive(Clone, Debug, CustomModule)]
+#[built(GatedFeedForward)]
+pub struct GatedFeedForwardConfig<Model: Dim, F: Dim, Act: std::fmt::Debug = SiLU> {
+    #[module]
+    pub gate: MatMulConfig<Model, F>,
+    #[module]
+    pub up: MatMulConfig<Model, F>,
+    #[module]
+    pub act: Act,
+    #[module]
+    pub drop1: Dropout,
+    #[module]
+    pub down: MatMulConfig<F, Model>,
+    #[module]
+    pub drop2: Dropout,
+}
+
+/// [GatedFeedForwardConfig] with [SiLU] gating.
+pub type SwiGLUConfig<Model, F> = GatedFeedForwardConfig<Model, F, SiLU>;
+
+/// [GatedFeedForwardConfig] with [GeLU] gating.
+pub type GeGLUConfig<Model, F> = GatedFeedForwardConfig<Model, F, GeLU>;
+
+impl<Model: Dim, F: Dim, Act: Default + std::fmt::Debug> GatedFeedForwardConfig<Model, F, Act> {
+    pub fn new(model: Model, f: F) -> Self {
+        GatedFeedForwardConfig {
+            gate: MatMulConfig { inp: model, out: f },
+            up: MatMulConfig { inp: model, out: f },
+            act: Default::default(),
+            drop1: Default::default(),
+            down: MatMulConfig { inp: f, out: model },
+            drop2: Default::default(),
+        }
+    }
+}
+
+impl<Model: Dim, F: Dim, Act: std::fmt::Debug> WithDropout
+    for GatedFeedForwardConfig<Model, F, Act>
+{
+    fn with_dropout(mut self, p: f64) -> Self {
+        self.drop1.p = p;
+        self.drop2.p = p;
+        self
+    }
+}
+
+impl<M: Dim, F: Dim, A: std::fmt::Debug, E: Dtype, D: Device<E>, X, Y> dfdx_nn_core::Module<X>
+    for GatedFeedForward<M, F, A, E, D>
+where
+    A: BuildOnDevice<E, D>,
+    X: WithEmptyTape,
+    Y: TryMul<Y, Output = Y> + HasErr<Err = D::Err>,
+    MatMul<M, F, E, D>: dfdx_nn_core::Module<X, Output = Y, Error = D::Err>,
+    A::Built: dfdx_nn_core::Module<Y, Output = Y, Error = D::Err>,
+    MatMul<F, M, E, D>: dfdx_nn_core::Module<Y, Output = X, Error = D::Err>,
+    Dropout: dfdx_nn_core::Module<Y, Output = Y, Error = D::Err>
+        + dfdx_nn_core::Module<X, Output = X, Error = D::Err>,
+{
+    type Output = X;
+    type Error = D::Err;
+
+    fn try_forward(&self, x: X) -> Result<Self::Output, D::Err> {
+        let gate = self.gate.try_forward(x.with_empty_tape())?;
+        let gate = self.act.try_forward(gate)?;
+        let up = self.up.try_forward(x)?;
+        let x = self.drop1.try_forward(gate.try_mul(up)?)?;
+        let x = self.down.try_forward(x)?;
+        self.drop2.try_forward(x)
+    }
+
+    fn try_forward_mut(&mut self, x: X) -> Result<Self::Output, D::Err> {
+        let gate = self.gate.try_forward_mut(x.with_empty_tape())?;
+        let gate = self.act.try_forward_mut(gate)?;
+        let up = self.up.try_forward_mut(x)?;
+        let x = self.drop1.try_forward_mut(gate.try_mul(up)?)?;
+        let x = self.down.try_forward_mut(x)?;
+        self.drop2.try_forward_mut(x)
+    }
+}
+
+/// Where the normalization layers of [EncoderBlock] & [DecoderBlock] are applied.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NormPlacement {
+    /// After each residual add, i.e. `norm(x + f(x))`. This is the original transformer.
+    #[default]
+    Post,
+    /// Inside each residual before its sublayer, i.e. `x + f(norm(x))`. Trains more stably in
+    /// deep stacks.
+    Pre,
+}
+
+/// One residual sublayer `f` along with its `norm`, i.e. `norm(x + f(x))` or `x + f(norm(x))`
+/// depending on `placement`.
+fn residual<X>(
+    placement: NormPlacement,
+    x: X,
+    norm: impl FnOnce(X) -> Result<X, X::Err>,
+    f: impl FnOnce(X) -> Result<X, X::Err>,
+) -> Result<X, X::Err>
+where
+    X: SplitTape + TryAdd<X::NoTape, Output = X> + HasErr,
+{
+    let (x, tape) = x.split_tape();
+    match placement {
+        NormPlacement::Post => norm(f(x.clone().put_tape(tape))?.try_add(x)?),
+        NormPlacement::Pre => f(norm(x.clone().put_tape(tape))?)?.try_add(x),
+    }
+}
+
+/// A transformer encoder block. `Norm` is the config of the normalization layers, e.g.
+/// [RMSNorm1DConfig] for LLaMA-style blocks (see [EncoderBlockConfig::with_norm]), and `FF` the
+/// config of the feed forward, e.g. [SwiGLUConfig] (see [EncoderBlockConfig::with_ff]).
+///
+/// The tensor names don't depend on [NormPlacement], so checkpoints load with either.
+#[derive(Clone, Debug, CustomModule)]
 #[built(EncoderBlock)]
-pub struct EncoderBlockConfig<Model: Dim, NumHeads: Dim, F: Dim> {
+pub struct EncoderBlockConfig<
+    Model: Dim,
+    NumHeads: Dim,
+    F: Dim,
+    Norm: std::fmt::Debug = LayerNorm1DConfig<Model>,
+    FF: std::fmt::Debug = FeedForwardConfig<Model, F>,
+> {
+    #[module]
     pub self_attn: ResidualAdd<MultiHeadAttentionConfig<Model, NumHeads>>,
-    pub norm1: LayerNorm1DConfig<Model>,
-    pub ff: ResidualAdd<FeedForwardConfig<Model, F>>,
-    pub norm2: LayerNorm1DConfig<Model>,
+    #[module]
+    pub norm1: Norm,
+    #[module]
+    pub ff: ResidualAdd<FF>,
+    #[module]
+    pub norm2: Norm,
+    pub norm_placement: NormPlacement,
 }
 
 impl<Model: Dim, NumHeads: Dim, F: Dim> EncoderBlockConfig<Model, NumHeads, F> {
@@ -31,90 +189,431 @@ impl<Model: Dim, NumHeads: Dim, F: Dim> EncoderBlockConfig<Model, NumHeads, F> {
                 model, num_heads, model, model,
             )),
             norm1: LayerNorm1DConfig(model),
-            ff: ResidualAdd(FeedForwardConfig {
-                l1: LinearConfig::new(model, f),
-                act1: ReLU,
-                l2: LinearConfig::new(f, model),
-            }),
+            ff: ResidualAdd(FeedForwardConfig::new(model, f)),
             norm2: LayerNorm1DConfig(model),
+            norm_placement: NormPlacement::Post,
+        }
+    }
+}
+
+impl<Model: Dim, NumHeads: Dim, F: Dim, Norm: std::fmt::Debug, FF: std::fmt::Debug>
+    EncoderBlockConfig<Model, NumHeads, F, Norm, FF>
+{
+    /// Replaces all normalization layers in the block with `norm`.
+    pub fn with_norm<N: Clone + std::fmt::Debug>(
+        self,
+        norm: N,
+    ) -> EncoderBlockConfig<Model, NumHeads, F, N, FF> {
+        EncoderBlockConfig {
+            self_attn: self.self_attn,
+            norm1: norm.clone(),
+            ff: self.ff,
+            norm2: norm,
+            norm_placement: self.norm_placement,
         }
     }
+
+    /// Replaces the feed forward of the block with `ff`, which should map `Model` back to `Model`.
+    pub fn with_ff<G: std::fmt::Debug>(
+        self,
+        ff: G,
+    ) -> EncoderBlockConfig<Model, NumHeads, F, Norm, G> {
+        EncoderBlockConfig {
+            self_attn: self.self_attn,
+            norm1: self.norm1,
+            ff: ResidualAdd(ff),
+            norm2: self.norm2,
+            norm_placement: self.norm_placement,
+        }
+    }
+
+    pub fn with_norm_placement(mut self, norm_placement: NormPlacement) -> Self {
+        self.norm_placement = norm_placement;
+        self
+    }
+
+    /// Sets the probability of all dropout in the block.
+    pub fn with_dropout(mut self, p: f64) -> Self
+    where
+        FF: WithDropout,
+    {
+        self.self_attn.0.dropout.p = p;
+        self.ff.0 = self.ff.0.with_dropout(p);
+        self
+    }
 }
 
+impl<
+        M: Dim,
+        H: Dim,
+        F: Dim,
+        N: std::fmt::Debug,
+        FF: std::fmt::Debug,
+        E: Dtype,
+        D: Device<E>,
+        X,
+    > dfdx_nn_core::Module<X> for EncoderBlock<M, H, F, N, FF, E, D>
+where
+    N: BuildOnDevice<E, D>,
+    FF: BuildOnDevice<E, D>,
+    X: SplitTape + TryAdd<X::NoTape, Output = X> + HasErr<Err = D::Err>,
+    MultiHeadAttention<M, H, M, M, E, D>: dfdx_nn_core::Module<X, Output = X, Error = D::Err>,
+    N::Built: dfdx_nn_core::Module<X, Output = X, Error = D::Err>,
+    FF::Built: dfdx_nn_core::Module<X, Output = X, Error = D::Err>,
+{
+    type Output = X;
+    type Error = D::Err;
+
+    fn try_forward(&self, x: X) -> Result<Self::Output, D::Err> {
+        let p = self.norm_placement;
+        let x = residual(
+            p,
+            x,
+            |x| self.norm1.try_forward(x),
+            |x| self.self_attn.0.try_forward(x),
+        )?;
+        residual(
+            p,
+            x,
+            |x| self.norm2.try_forward(x),
+            |x| self.ff.0.try_forward(x),
+        )
+    }
+
+    fn try_forward_mut(&mut self, x: X) -> Result<Self::Output, D::Err> {
+        let p = self.norm_placement;
+        let x = residual(
+            p,
+            x,
+            |x| self.norm1.try_forward_mut(x),
+            |x| self.self_attn.0.try_forward_mut(x),
+        )?;
+        residual(
+            p,
+            x,
+            |x| self.norm2.try_forward_mut(x),
+            |x| self.ff.0.try_forward_mut(x),
+        )
+    }
+}
+
+impl<M: Dim, H: Dim, F: Dim, N: std::fmt::Debug, FF: std::fmt::Debug, E: Dtype, D: Device<E>>
+    EncoderBlock<M, H, F, N, FF, E, D>
+where
+    N: BuildOnDevice<E, D>,
+    FF: BuildOnDevice<E, D>,
+{
+    /// Like [dfdx_nn_core::Module::try_forward], with `mask` (e.g. an [AttentionMask] of key
+    /// padding) applied to the self attention.
+    pub fn try_forward_masked<X, Mask>(&self, x: X, mask: Mask) -> Result<X, D::Err>
+    where
+        X: SplitTape + TryAdd<X::NoTape, Output = X> + HasErr<Err = D::Err>,
+        MultiHeadAttention<M, H, M, M, E, D>:
+            dfdx_nn_core::Module<(X, Mask), Output = X, Error = D::Err>,
+        N::Built: dfdx_nn_core::Module<X, Output = X, Error = D::Err>,
+        FF::Built: dfdx_nn_core::Module<X, Output = X, Error = D::Err>,
+    {
+        let p = self.norm_placement;
+        let x = residual(
+            p,
+            x,
+            |x| self.norm1.try_forward(x),
+            |x| self.self_attn.0.try_forward((x, mask)),
+        )?;
+        residual(
+            p,
+            x,
+            |x| self.norm2.try_forward(x),
+            |x| self.ff.0.try_forward(x),
+        )
+    }
+
+    /// Like [dfdx_nn_core::Module::try_forward_mut], with `mask` applied to the self attention.
+    pub fn try_forward_masked_mut<X, Mask>(&mut self, x: X, mask: Mask) -> Result<X, D::Err>
+    where
+        X: SplitTape + TryAdd<X::NoTape, Output = X> + HasErr<Err = D::Err>,
+        MultiHeadAttention<M, H, M, M, E, D>:
+            dfdx_nn_core::Module<(X, Mask), Output = X, Error = D::Err>,
+        N::Built: dfdx_nn_core::Module<X, Output = X, Error = D::Err>,
+        FF::Built: dfdx_nn_core::Module<X, Output = X, Error = D::Err>,
+    {
+        let p = self.norm_placement;
+        let x = residual(
+            p,
+            x,
+            |x| self.norm1.try_forward_mut(x),
+            |x| self.self_attn.0.try_forward_mut((x, mask)),
+        )?;
+        residual(
+            p,
+            x,
+            |x| self.norm2.try_forward_mut(x),
+            |x| self.ff.0.try_forward_mut(x),
+        )
+    }
+}
+
+/// A transformer decoder block. `Norm` & `FF` are the configs of the normalization layers and
+/// the feed forward, like [EncoderBlockConfig].
+///
+/// [DecoderBlockConfig::new] makes `self_attn` causal, so each target token only attends to the
+/// ones before it.
 #[derive(Clone, Debug, CustomModule)]
 #[built(DecoderBlock)]
-pub struct DecoderBlockConfig<Model: Dim, NumHeads: Dim, F: Dim> {
+pub struct DecoderBlockConfig<
+    Model: Dim,
+    NumHeads: Dim,
+    F: Dim,
+    Norm: std::fmt::Debug = LayerNorm1DConfig<Model>,
+    FF: std::fmt::Debug = FeedForwardConfig<Model, F>,
+> {
     #[module]
     pub self_attn: ResidualAdd<MultiHeadAttentionConfig<Model, NumHeads>>,
     #[module]
-    pub norm1: LayerNorm1DConfig<Model>,
+    pub norm1: Norm,
     #[module]
     pub mh_attn: MultiHeadAttentionConfig<Model, NumHeads>,
     #[module]
-    pub norm2: LayerNorm1DConfig<Model>,
+    pub norm2: Norm,
     #[module]
-    pub ff: ResidualAdd<FeedForwardConfig<Model, F>>,
+    pub ff: ResidualAdd<FF>,
     #[module]
-    pub norm3: LayerNorm1DConfig<Model>,
+    pub norm3: Norm,
+    pub norm_placement: NormPlacement,
 }
 
 impl<Model: Dim, NumHeads: Dim, F: Dim> DecoderBlockConfig<Model, NumHeads, F> {
     pub fn new(model: Model, num_heads: NumHeads, f: F) -> Self {
         DecoderBlockConfig {
-            self_attn: ResidualAdd(MultiHeadAttentionConfig::new(
-                model, num_heads, model, model,
-            )),
+            self_attn: ResidualAdd(
+                MultiHeadAttentionConfig::new(model, num_heads, model, model).with_causal(true),
+            ),
             norm1: LayerNorm1DConfig(model),
             mh_attn: MultiHeadAttentionConfig::new(model, num_heads, model, model),
             norm2: LayerNorm1DConfig(model),
-            ff: ResidualAdd(FeedForwardConfig {
-                l1: LinearConfig::new(model, f),
-                act1: ReLU,
-                l2: LinearConfig::new(f, model),
-            }),
+            ff: ResidualAdd(FeedForwardConfig::new(model, f)),
             norm3: LayerNorm1DConfig(model),
+            norm_placement: NormPlacement::Post,
         }
     }
 }
 
-impl<M: Dim, H: Dim, F: Dim, E: Dtype, D: Device<E>, Tgt, Mem> dfdx_nn_core::Module<(Tgt, Mem)>
-    for DecoderBlock<M, H, F, E, D>
+impl<Model: Dim, NumHeads: Dim, F: Dim, Norm: std::fmt::Debug, FF: std::fmt::Debug>
+    DecoderBlockConfig<Model, NumHeads, F, Norm, FF>
+{
+    /// Replaces all normalization layers in the block with `norm`.
+    pub fn with_norm<N: Clone + std::fmt::Debug>(
+        self,
+        norm: N,
+    ) -> DecoderBlockConfig<Model, NumHeads, F, N, FF> {
+        DecoderBlockConfig {
+            self_attn: self.self_attn,
+            norm1: norm.clone(),
+            mh_attn: self.mh_attn,
+            norm2: norm.clone(),
+            ff: self.ff,
+            norm3: norm,
+            norm_placement: self.norm_placement,
+        }
+    }
+
+    /// Replaces the feed forward of the block with `ff`, which should map `Model` back to `Model`.
+    pub fn with_ff<G: std::fmt::Debug>(
+        self,
+        ff: G,
+    ) -> DecoderBlockConfig<Model, NumHeads, F, Norm, G> {
+        DecoderBlockConfig {
+            self_attn: self.self_attn,
+            norm1: self.norm1,
+            mh_attn: self.mh_attn,
+            norm2: self.norm2,
+            ff: ResidualAdd(ff),
+            norm3: self.norm3,
+            norm_placement: self.norm_placement,
+        }
+    }
+
+    pub fn with_norm_placement(mut self, norm_placement: NormPlacement) -> Self {
+        self.norm_placement = norm_placement;
+        self
+    }
+
+    /// Sets the probability of all dropout in the block.
+    pub fn with_dropout(mut self, p: f64) -> Self
+    where
+        FF: WithDropout,
+    {
+        self.self_attn.0.dropout.p = p;
+        self.mh_attn.dropout.p = p;
+        self.ff.0 = self.ff.0.with_dropout(p);
+        self
+    }
+}
+
+impl<
+        M: Dim,
+        H: Dim,
+        F: Dim,
+        N: std::fmt::Debug,
+        FF: std::fmt::Debug,
+        E: Dtype,
+        D: Device<E>,
+        Tgt,
+        Mem,
+    > dfdx_nn_core::Module<(Tgt, Mem)> for DecoderBlock<M, H, F, N, FF, E, D>
 where
-    Tgt: WithEmptyTape + SplitTape + TryAdd<Tgt::NoTape, Output = Tgt> + HasErr<Err = D::Err>,
+    N: BuildOnDevice<E, D>,
+    FF: BuildOnDevice<E, D>,
+    Tgt: SplitTape + TryAdd<Tgt::NoTape, Output = Tgt> + HasErr<Err = D::Err>,
     Mem: Clone,
-    ResidualAdd<MultiHeadAttention<M, H, M, M, E, D>>:
-        dfdx_nn_core::Module<Tgt, Output = Tgt, Error = D::Err>,
-    MultiHeadAttention<M, H, M, M, E, D>:
-        dfdx_nn_core::Module<(Tgt, Mem, Mem), Output = Tgt, Error = D::Err>,
-    LayerNorm1D<M, E, D>: dfdx_nn_core::Module<Tgt, Output = Tgt, Error = D::Err>,
-    ResidualAdd<FeedForward<M, F, E, D>>: dfdx_nn_core::Module<Tgt, Output = Tgt, Error = D::Err>,
+    MultiHeadAttention<M, H, M, M, E, D>: dfdx_nn_core::Module<Tgt, Output = Tgt, Error = D::Err>
+        + dfdx_nn_core::Module<(Tgt, Mem, Mem), Output = Tgt, Error = D::Err>,
+    N::Built: dfdx_nn_core::Module<Tgt, Output = Tgt, Error = D::Err>,
+    FF::Built: dfdx_nn_core::Module<Tgt, Output = Tgt, Error = D::Err>,
 {
     type Output = Tgt;
     type Error = D::Err;
 
     fn try_forward(&self, (tgt, mem): (Tgt, Mem)) -> Result<Self::Output, D::Err> {
-        let x = self.self_attn.try_forward(tgt)?;
-        let x = self.norm1.try_forward(x)?;
+        let p = self.norm_placement;
+        let x = residual(
+            p,
+            tgt,
+            |x| self.norm1.try_forward(x),
+            |x| self.self_attn.0.try_forward(x),
+        )?;
+        let x = residual(
+            p,
+            x,
+            |x| self.norm2.try_forward(x),
+            |x| self.mh_attn.try_forward((x, mem.clone(), mem)),
+        )?;
+        residual(
+            p,
+            x,
+            |x| self.norm3.try_forward(x),
+            |x| self.ff.0.try_forward(x),
+        )
+    }
+
+    fn try_forward_mut(&mut self, (tgt, mem): (Tgt, Mem)) -> Result<Self::Output, D::Err> {
+        let p = self.norm_placement;
+        let x = residual(
+            p,
+            tgt,
+            |x| self.norm1.try_forward_mut(x),
+            |x| self.self_attn.0.try_forward_mut(x),
+        )?;
+        let x = residual(
+            p,
+            x,
+            |x| self.norm2.try_forward_mut(x),
+            |x| self.mh_attn.try_forward_mut((x, mem.clone(), mem)),
+        )?;
+        residual(
+            p,
+            x,
+            |x| self.norm3.try_forward_mut(x),
+            |x| self.ff.0.try_forward_mut(x),
+        )
+    }
+}
+
+/// Incremental decoding of new target tokens, using & extending the [KVCache] of `self_attn`.
+/// Returns the output for the new tokens along with the updated cache.
+impl<
+        M: Dim,
+        H: Dim,
+        F: Dim,
+        N: std::fmt::Debug,
+        FF: std::fmt::Debug,
+        E: Dtype,
+        D: Device<E>,
+        B: Dim,
+        S1: Dim,
+        Mem,
+    >
+    dfdx_nn_core::Module<(
+        Tensor<(B, S1, M), E, D>,
+        Mem,
+        Option<KVCache<B, M, M, E, D>>,
+    )> for DecoderBlock<M, H, F, N, FF, E, D>
+where
+    N: BuildOnDevice<E, D>,
+    FF: BuildOnDevice<E, D>,
+    Mem: Clone,
+    MultiHeadAttention<M, H, M, M, E, D>: dfdx_nn_core::Module<
+            (Tensor<(B, S1, M), E, D>, Option<KVCache<B, M, M, E, D>>),
+            Output = (Tensor<(B, S1, M), E, D>, KVCache<B, M, M, E, D>),
+            Error = D::Err,
+        > + dfdx_nn_core::Module<
+            (Tensor<(B, S1, M), E, D>, Mem, Mem),
+            Output = Tensor<(B, S1, M), E, D>,
+            Error = D::Err,
+        >,
+    N::Built: dfdx_nn_core::Module<
+        Tensor<(B, S1, M), E, D>,
+        Output = Tensor<(B, S1, M), E, D>,
+        Error = D::Err,
+    >,
+    FF::Built: dfdx_nn_core::Module<
+        Tensor<(B, S1, M), E, D>,
+        Output = Tensor<(B, S1, M), E, D>,
+        Error = D::Err,
+    >,
+{
+    type Output = (Tensor<(B, S1, M), E, D>, KVCache<B, M, M, E, D>);
+    type Error = D::Err;
 
-        let (x, tape) = x.split_tape();
-        let x_residual = x.clone();
-        let x = self
-            .mh_attn
-            .try_forward((x.put_tape(tape), mem.clone(), mem))?;
-        let x = x.try_add(x_residual)?;
-        let x = self.norm2.try_forward(x)?;
-        let x = self.ff.try_forward(x)?;
-        self.norm3.try_forward(x)
+    fn try_forward(
+        &self,
+        (tgt, mem, cache): (
+            Tensor<(B, S1, M), E, D>,
+            Mem,
+            Option<KVCache<B, M, M, E, D>>,
+        ),
+    ) -> Result<Self::Output, D::Err> {
+        let p = self.norm_placement;
+        let mut new_cache = None;
+        let x = residual(
+            p,
+            tgt,
+            |x| self.norm1.try_forward(x),
+            |x| {
+                let (y, cache) = self.self_attn.0.try_forward((x, cache))?;
+                new_cache = Some(cache);
+                Ok(y)
+            },
+        )?;
+        let x = residual(
+            p,
+            x,
+            |x| self.norm2.try_forward(x),
+            |x| self.mh_attn.try_forward((x, mem.clone(), mem)),
+        )?;
+        let x = residual(
+            p,
+            x,
+            |x| self.norm3.try_forward(x),
+            |x| self.ff.0.try_forward(x),
+        )?;
+        Ok((x, new_cache.unwrap()))
     }
 }
 
 #[derive(Clone, Debug, CustomModule)]
 #[built(Transformer)]
-pub struct TransformerConfig<Model: Dim, NumHeads: Dim, F: Dim> {
+pub struct TransformerConfig<
+    Model: Dim,
+    NumHeads: Dim,
+    F: Dim,
+    Norm: std::fmt::Debug = LayerNorm1DConfig<Model>,
+    FF: std::fmt::Debug = FeedForwardConfig<Model, F>,
+> {
     #[module]
-    pub encoder: Vec<EncoderBlockConfig<Model, NumHeads, F>>,
+    pub encoder: Vec<EncoderBlockConfig<Model, NumHeads, F, Norm, FF>>,
     #[module]
-    pub decoder: Vec<DecoderBlockConfig<Model, NumHeads, F>>,
+    pub decoder: Vec<DecoderBlockConfig<Model, NumHeads, F, Norm, FF>>,
 }
 
 impl<Model: Dim, NumHeads: Dim, F: Dim> TransformerConfig<Model, NumHeads, F> {
@@ -137,11 +636,95 @@ impl<Model: Dim, NumHeads: Dim, F: Dim> TransformerConfig<Model, NumHeads, F> {
     }
 }
 
-impl<M: Dim, H: Dim, F: Dim, E: Dtype, D: Device<E>, Src: SplitTape, Tgt: PutTape<Src::Tape>>
-    dfdx_nn_core::Module<(Src, Tgt)> for Transformer<M, H, F, E, D>
+impl<Model: Dim, NumHeads: Dim, F: Dim, Norm: std::fmt::Debug, FF: std::fmt::Debug>
+    TransformerConfig<Model, NumHeads, F, Norm, FF>
+{
+    /// Replaces all normalization layers in every encoder and decoder block with `norm`.
+    pub fn with_norm<N: Clone + std::fmt::Debug>(
+        self,
+        norm: N,
+    ) -> TransformerConfig<Model, NumHeads, F, N, FF> {
+        TransformerConfig {
+            encoder: self
+                .encoder
+                .into_iter()
+                .map(|b| b.with_norm(norm.clone()))
+                .collect(),
+            decoder: self
+                .decoder
+                .into_iter()
+                .map(|b| b.with_norm(norm.clone()))
+                .collect(),
+        }
+    }
+
+    /// Replaces the feed forward of every encoder and decoder block with `ff`.
+    pub fn with_ff<G: Clone + std::fmt::Debug>(
+        self,
+        ff: G,
+    ) -> TransformerConfig<Model, NumHeads, F, Norm, G> {
+        TransformerConfig {
+            encoder: self
+                .encoder
+                .into_iter()
+                .map(|b| b.with_ff(ff.clone()))
+                .collect(),
+            decoder: self
+                .decoder
+                .into_iter()
+                .map(|b| b.with_ff(ff.clone()))
+                .collect(),
+        }
+    }
+
+    pub fn with_norm_placement(mut self, norm_placement: NormPlacement) -> Self {
+        self.encoder = self
+            .encoder
+            .into_iter()
+            .map(|b| b.with_norm_placement(norm_placement))
+            .collect();
+        self.decoder = self
+            .decoder
+            .into_iter()
+            .map(|b| b.with_norm_placement(norm_placement))
+            .collect();
+        self
+    }
+
+    /// Sets the probability of all dropout in every encoder and decoder block.
+    pub fn with_dropout(mut self, p: f64) -> Self
+    where
+        FF: WithDropout,
+    {
+        self.encoder = self
+            .encoder
+            .into_iter()
+            .map(|b| b.with_dropout(p))
+            .collect();
+        self.decoder = self
+            .decoder
+            .into_iter()
+            .map(|b| b.with_dropout(p))
+            .collect();
+        self
+    }
+}
+
+impl<
+        M: Dim,
+        H: Dim,
+        F: Dim,
+        N: BuildOnDevice<E, D> + std::fmt::Debug,
+        FF: BuildOnDevice<E, D> + std::fmt::Debug,
+        E: Dtype,
+        D: Device<E>,
+        Src: SplitTape,
+        Tgt: PutTape<Src::Tape>,
+    > dfdx_nn_core::Module<(Src, Tgt)> for Transformer<M, H, F, N, FF, E, D>
 where
-    Vec<EncoderBlock<M, H, F, E, D>>: dfdx_nn_core::Module<Src, Output = Src, Error = D::Err>,
-    DecoderBlock<M, H, F, E, D>: dfdx_nn_core::Module<
+    Vec<EncoderBlock<M, H, F, N, FF, E, D>>:
+        dfdx_nn_core::Module<Src, Output = Src, Error = D::Err>,
+    DecoderBlock<M, H, F, N, FF, E, D>: dfdx_nn_core::Module<
         (<Tgt as PutTape<Src::Tape>>::Output, Src::NoTape),
         Output = <Tgt as PutTape<Src::Tape>>::Output,
         Error = D::Err,
@@ -158,4 +741,13 @@ where
         }
         Ok(tgt)
     }
+
+    fn try_forward_mut(&mut self, (src, tgt): (Src, Tgt)) -> Result<Self::Output, D::Err> {
+        let (mem, tape) = self.encoder.try_forward_mut(src)?.split_tape();
+        let mut tgt = tgt.put_tape(tape);
+        for block in self.decoder.iter_mut() {
+            tgt = block.try_forward_mut((tgt, mem.clone()))?;
+        }
+        Ok(tgt)
+    }
 }
@@ -0,0 +1,511 @@
+use crate::*;
+use dfdx::prelude::*;
+use num_traits::Float;
+
+/// The head of [TransformerEncoderConfig::new], a BERT-style pooler: the output of the first
+/// token through a linear layer and a tanh.
+pub type PoolerConfig<Model> = (ClsPool, LinearConfig<Model, Model>, Tanh);
+
+/// A BERT-style encoder only transformer: token & position embeddings, a stack of
+/// [EncoderBlock]s and a head. This is the encoder half of [TransformerConfig], without needing
+/// a target.
+///
+/// Takes `(S,)` or `(B, S)` token ids, or already embedded `(S, M)` or `(B, S, M)` inputs (see
+/// [EmbeddedDtype]), which skip the embedding. Either can come with an [AttentionMask] of key
+/// padding for every block and the head:
+/// ```ignore
+/// let mask = AttentionMask::new().with_key_padding(padding);
+/// let pooled = encoder.forward((ids, mask));
+/// ```
+/// [TransformerEncoderConfig::new] pools the output with [PoolerConfig]. Use
+/// [TransformerEncoderConfig::with_head] for other heads, e.g. [MeanPool], which only averages
+/// the tokens that aren't padding when given the mask.
+#[derive(Clone, Debug, CustomModule)]
+#[built(TransformerEncoder)]
+pub struct TransformerEncoderConfig<
+    Vocab: Dim,
+    Model: Dim,
+    NumHeads: Dim,
+    F: Dim,
+    Norm: std::fmt::Debug = LayerNorm1DConfig<Model>,
+    FF: std::fmt::Debug = FeedForwardConfig<Model, F>,
+    Pos: std::fmt::Debug = LearnedPositionalEmbeddingConfig<usize, Model>,
+    Head: std::fmt::Debug = PoolerConfig<Model>,
+> {
+    #[module]
+    pub embedding: EmbeddingConfig<Vocab, Model>,
+    #[module]
+    pub pos: Pos,
+    #[module]
+    pub blocks: Vec<EncoderBlockConfig<Model, NumHeads, F, Norm, FF>>,
+    #[module]
+    pub head: Head,
+}
+
+impl<Vocab: Dim, Model: Dim, NumHeads: Dim, F: Dim>
+    TransformerEncoderConfig<Vocab, Model, NumHeads, F>
+{
+    pub fn new(
+        vocab: Vocab,
+        model: Model,
+        num_heads: NumHeads,
+        f: F,
+        num_layers: usize,
+        max_len: usize,
+    ) -> Self {
+        let mut blocks = Vec::with_capacity(num_layers);
+        for _ in 0..num_layers {
+            blocks.push(EncoderBlockConfig::new(model, num_heads, f));
+        }
+        TransformerEncoderConfig {
+            embedding: EmbeddingConfig::new(vocab, model),
+            pos: LearnedPositionalEmbeddingConfig { max_len, model },
+            blocks,
+            head: (ClsPool, LinearConfig::new(model, model), Tanh),
+        }
+    }
+}
+
+impl<
+        Vocab: Dim,
+        Model: Dim,
+        NumHeads: Dim,
+        F: Dim,
+        Norm: std::fmt::Debug,
+        FF: std::fmt::Debug,
+        Pos: std::fmt::Debug,
+        Head: std::fmt::Debug,
+    > TransformerEncoderConfig<Vocab, Model, NumHeads, F, Norm, FF, Pos, Head>
+{
+    /// Replaces all normalization layers in every block with `norm`.
+    pub fn with_norm<N: Clone + std::fmt::Debug>(
+        self,
+        norm: N,
+    ) -> TransformerEncoderConfig<Vocab, Model, NumHeads, F, N, FF, Pos, Head> {
+        TransformerEncoderConfig {
+            embedding: self.embedding,
+            pos: self.pos,
+            blocks: self
+                .blocks
+                .into_iter()
+                .map(|b| b.with_norm(norm.clone()))
+                .collect(),
+            head: self.head,
+        }
+    }
+
+    /// Replaces the feed forward of every block with `ff`.
+    pub fn with_ff<G: Clone + std::fmt::Debug>(
+        self,
+        ff: G,
+    ) -> TransformerEncoderConfig<Vocab, Model, NumHeads, F, Norm, G, Pos, Head> {
+        TransformerEncoderConfig {
+            embedding: self.embedding,
+            pos: self.pos,
+            blocks: self
+                .blocks
+                .into_iter()
+                .map(|b| b.with_ff(ff.clone()))
+                .collect(),
+            head: self.head,
+        }
+    }
+
+    /// Replaces the position embedding with `pos`, e.g. [SinusoidalPositionalEncodingConfig].
+    pub fn with_pos<P: std::fmt::Debug>(
+        self,
+        pos: P,
+    ) -> TransformerEncoderConfig<Vocab, Model, NumHeads, F, Norm, FF, P, Head> {
+        TransformerEncoderConfig {
+            embedding: self.embedding,
+            pos,
+            blocks: self.blocks,
+            head: self.head,
+        }
+    }
+
+    /// Replaces the head with `head`, e.g. [MeanPool] or `(ClsPool, LinearConfig<Model, C>)`.
+    pub fn with_head<Hd: std::fmt::Debug>(
+        self,
+        head: Hd,
+    ) -> TransformerEncoderConfig<Vocab, Model, NumHeads, F, Norm, FF, Pos, Hd> {
+        TransformerEncoderConfig {
+            embedding: self.embedding,
+            pos: self.pos,
+            blocks: self.blocks,
+            head,
+        }
+    }
+
+    pub fn with_norm_placement(mut self, norm_placement: NormPlacement) -> Self {
+        self.blocks = self
+            .blocks
+            .into_iter()
+            .map(|b| b.with_norm_placement(norm_placement))
+            .collect();
+        self
+    }
+
+    /// Sets the probability of all dropout in every block.
+    pub fn with_dropout(mut self, p: f64) -> Self
+    where
+        FF: WithDropout,
+    {
+        self.blocks = self.blocks.into_iter().map(|b| b.with_dropout(p)).collect();
+        self
+    }
+}
+
+/// The dtypes [TransformerEncoder] takes already embedded `(S, M)` & `(B, S, M)` inputs in.
+///
+/// Implemented for each float rather than for every [Float], so the compiler can tell `(S, M)`
+/// embeddings apart from `(B, S)` token ids, which are `usize`.
+pub trait EmbeddedDtype: Dtype + Float {}
+impl EmbeddedDtype for f32 {}
+impl EmbeddedDtype for f64 {}
+
+impl<
+        V: Dim,
+        M: Dim,
+        H: Dim,
+        F: Dim,
+        N: std::fmt::Debug,
+        FF: std::fmt::Debug,
+        P: std::fmt::Debug,
+        Hd: std::fmt::Debug,
+        E: EmbeddedDtype,
+        D: Device<E>,
+        S: Dim,
+        T: Tape<E, D>,
+    > Module<Tensor<(S, M), E, D, T>> for TransformerEncoder<V, M, H, F, N, FF, P, Hd, E, D>
+where
+    P: BuildOnDevice<E, D>,
+    P::Built: Module<Tensor<(S, M), E, D, T>, Output = Tensor<(S, M), E, D, T>, Error = D::Err>,
+    Vec<EncoderBlock<M, H, F, N, FF, E, D>>:
+        Module<Tensor<(S, M), E, D, T>, Output = Tensor<(S, M), E, D, T>, Error = D::Err>,
+    Hd: BuildOnDevice<E, D>,
+    Hd::Built: Module<Tensor<(S, M), E, D, T>, Error = D::Err>,
+{
+    type Output = <Hd::Built as Module<Tensor<(S, M), E, D, T>>>::Output;
+    type Error = D::Err;
+
+    /// Forward of already embedded tokens, skipping [TransformerEncoder::embedding]
+    fn try_forward(&self, x: Tensor<(S, M), E, D, T>) -> Result<Self::Output, D::Err> {
+        let x = self.pos.try_forward(x)?;
+        let x = self.blocks.try_forward(x)?;
+        self.head.try_forward(x)
+    }
+
+    fn try_forward_mut(&mut self, x: Tensor<(S, M), E, D, T>) -> Result<Self::Output, D::Err> {
+        let x = self.pos.try_forward_mut(x)?;
+        let x = self.blocks.try_forward_mut(x)?;
+        self.head.try_forward_mut(x)
+    }
+}
+
+impl<
+        V: Dim,
+        M: Dim,
+        H: Dim,
+        F: Dim,
+        N: std::fmt::Debug,
+        FF: std::fmt::Debug,
+        P: std::fmt::Debug,
+        Hd: std::fmt::Debug,
+        E: Dtype,
+        D: Device<E>,
+        S: Dim,
+        T: Tape<E, D>,
+    > Module<Tensor<(S,), usize, D, T>> for TransformerEncoder<V, M, H, F, N, FF, P, Hd, E, D>
+where
+    Self: Module<Tensor<(S, M), E, D, T>, Error = D::Err>,
+{
+    type Output = <Self as Module<Tensor<(S, M), E, D, T>>>::Output;
+    type Error = D::Err;
+
+    fn try_forward(&self, x: Tensor<(S,), usize, D, T>) -> Result<Self::Output, D::Err> {
+        let x = self.embedding.try_forward(x)?;
+        self.try_forward(x)
+    }
+
+    fn try_forward_mut(&mut self, x: Tensor<(S,), usize, D, T>) -> Result<Self::Output, D::Err> {
+        let x = self.embedding.try_forward_mut(x)?;
+        self.try_forward_mut(x)
+    }
+}
+
+impl<
+        V: Dim,
+        M: Dim,
+        H: Dim,
+        F: Dim,
+        N: std::fmt::Debug,
+        FF: std::fmt::Debug,
+        P: std::fmt::Debug,
+        Hd: std::fmt::Debug,
+        E: EmbeddedDtype,
+        D: Device<E>,
+        B: Dim,
+        S: Dim,
+        T: Tape<E, D>,
+    > Module<Tensor<(B, S, M), E, D, T>> for TransformerEncoder<V, M, H, F, N, FF, P, Hd, E, D>
+where
+    P: BuildOnDevice<E, D>,
+    P::Built:
+        Module<Tensor<(B, S, M), E, D, T>, Output = Tensor<(B, S, M), E, D, T>, Error = D::Err>,
+    Vec<EncoderBlock<M, H, F, N, FF, E, D>>:
+        Module<Tensor<(B, S, M), E, D, T>, Output = Tensor<(B, S, M), E, D, T>, Error = D::Err>,
+    Hd: BuildOnDevice<E, D>,
+    Hd::Built: Module<Tensor<(B, S, M), E, D, T>, Error = D::Err>,
+{
+    type Output = <Hd::Built as Module<Tensor<(B, S, M), E, D, T>>>::Output;
+    type Error = D::Err;
+
+    /// Batched forward of already embedded tokens, skipping [TransformerEncoder::embedding]
+    fn try_forward(&self, x: Tensor<(B, S, M), E, D, T>) -> Result<Self::Output, D::Err> {
+        let x = self.pos.try_forward(x)?;
+        let x = self.blocks.try_forward(x)?;
+        self.head.try_forward(x)
+    }
+
+    fn try_forward_mut(&mut self, x: Tensor<(B, S, M), E, D, T>) -> Result<Self::Output, D::Err> {
+        let x = self.pos.try_forward_mut(x)?;
+        let x = self.blocks.try_forward_mut(x)?;
+        self.head.try_forward_mut(x)
+    }
+}
+
+impl<
+        V: Dim,
+        M: Dim,
+        H: Dim,
+        F: Dim,
+        N: std::fmt::Debug,
+        FF: std::fmt::Debug,
+        P: std::fmt::Debug,
+        Hd: std::fmt::Debug,
+        E: Dtype,
+        D: Device<E>,
+        B: Dim,
+        S: Dim,
+        T: Tape<E, D>,
+    > Module<Tensor<(B, S), usize, D, T>> for TransformerEncoder<V, M, H, F, N, FF, P, Hd, E, D>
+where
+    Self: Module<Tensor<(B, S, M), E, D, T>, Error = D::Err>,
+{
+    type Output = <Self as Module<Tensor<(B, S, M), E, D, T>>>::Output;
+    type Error = D::Err;
+
+    fn try_forward(&self, x: Tensor<(B, S), usize, D, T>) -> Result<Self::Output, D::Err> {
+        let x = self.embedding.try_forward(x)?;
+        self.try_forward(x)
+    }
+
+    fn try_forward_mut(&mut self, x: Tensor<(B, S), usize, D, T>) -> Result<Self::Output, D::Err> {
+        let x = self.embedding.try_forward_mut(x)?;
+        self.try_forward_mut(x)
+    }
+}
+
+impl<
+        V: Dim,
+        M: Dim,
+        H: Dim,
+        F: Dim,
+        N: std::fmt::Debug,
+        FF: std::fmt::Debug,
+        P: std::fmt::Debug,
+        Hd: std::fmt::Debug,
+        E: EmbeddedDtype,
+        D: Device<E>,
+        S: Dim,
+        T: Tape<E, D>,
+    > Module<(Tensor<(S, M), E, D, T>, AttentionMask<Const<1>, S, S, E, D>)>
+    for TransformerEncoder<V, M, H, F, N, FF, P, Hd, E, D>
+where
+    N: BuildOnDevice<E, D>,
+    FF: BuildOnDevice<E, D>,
+    P: BuildOnDevice<E, D>,
+    P::Built: Module<Tensor<(S, M), E, D, T>, Output = Tensor<(S, M), E, D, T>, Error = D::Err>,
+    N::Built: Module<Tensor<(S, M), E, D, T>, Output = Tensor<(S, M), E, D, T>, Error = D::Err>,
+    FF::Built: Module<Tensor<(S, M), E, D, T>, Output = Tensor<(S, M), E, D, T>, Error = D::Err>,
+    Hd: BuildOnDevice<E, D>,
+    Hd::Built:
+        Module<(Tensor<(S, M), E, D, T>, AttentionMask<Const<1>, S, S, E, D>), Error = D::Err>,
+{
+    type Output = <Hd::Built as Module<(
+        Tensor<(S, M), E, D, T>,
+        AttentionMask<Const<1>, S, S, E, D>,
+    )>>::Output;
+    type Error = D::Err;
+
+    /// Forward of already embedded tokens with the same `mask` applied to the self attention of
+    /// every block and the head
+    fn try_forward(
+        &self,
+        (x, mask): (Tensor<(S, M), E, D, T>, AttentionMask<Const<1>, S, S, E, D>),
+    ) -> Result<Self::Output, D::Err> {
+        let mut x = self.pos.try_forward(x)?;
+        for block in self.blocks.iter() {
+            x = block.try_forward_masked(x, mask.clone())?;
+        }
+        self.head.try_forward((x, mask))
+    }
+
+    fn try_forward_mut(
+        &mut self,
+        (x, mask): (Tensor<(S, M), E, D, T>, AttentionMask<Const<1>, S, S, E, D>),
+    ) -> Result<Self::Output, D::Err> {
+        let mut x = self.pos.try_forward_mut(x)?;
+        for block in self.blocks.iter_mut() {
+            x = block.try_forward_masked_mut(x, mask.clone())?;
+        }
+        self.head.try_forward_mut((x, mask))
+    }
+}
+
+impl<
+        V: Dim,
+        M: Dim,
+        H: Dim,
+        F: Dim,
+        N: std::fmt::Debug,
+        FF: std::fmt::Debug,
+        P: std::fmt::Debug,
+        Hd: std::fmt::Debug,
+        E: Dtype,
+        D: Device<E>,
+        S: Dim,
+        T: Tape<E, D>,
+    >
+    Module<(
+        Tensor<(S,), usize, D, T>,
+        AttentionMask<Const<1>, S, S, E, D>,
+    )> for TransformerEncoder<V, M, H, F, N, FF, P, Hd, E, D>
+where
+    Self: Module<(Tensor<(S, M), E, D, T>, AttentionMask<Const<1>, S, S, E, D>), Error = D::Err>,
+{
+    type Output =
+        <Self as Module<(Tensor<(S, M), E, D, T>, AttentionMask<Const<1>, S, S, E, D>)>>::Output;
+    type Error = D::Err;
+
+    fn try_forward(
+        &self,
+        (x, mask): (
+            Tensor<(S,), usize, D, T>,
+            AttentionMask<Const<1>, S, S, E, D>,
+        ),
+    ) -> Result<Self::Output, D::Err> {
+        let x = self.embedding.try_forward(x)?;
+        self.try_forward((x, mask))
+    }
+
+    fn try_forward_mut(
+        &mut self,
+        (x, mask): (
+            Tensor<(S,), usize, D, T>,
+            AttentionMask<Const<1>, S, S, E, D>,
+        ),
+    ) -> Result<Self::Output, D::Err> {
+        let x = self.embedding.try_forward_mut(x)?;
+        self.try_forward_mut((x, mask))
+    }
+}
+
+impl<
+        V: Dim,
+        M: Dim,
+        H: Dim,
+        F: Dim,
+        N: std::fmt::Debug,
+        FF: std::fmt::Debug,
+        P: std::fmt::Debug,
+        Hd: std::fmt::Debug,
+        E: EmbeddedDtype,
+        D: Device<E>,
+        B: Dim,
+        S: Dim,
+        T: Tape<E, D>,
+    > Module<(Tensor<(B, S, M), E, D, T>, AttentionMask<B, S, S, E, D>)>
+    for TransformerEncoder<V, M, H, F, N, FF, P, Hd, E, D>
+where
+    N: BuildOnDevice<E, D>,
+    FF: BuildOnDevice<E, D>,
+    P: BuildOnDevice<E, D>,
+    P::Built:
+        Module<Tensor<(B, S, M), E, D, T>, Output = Tensor<(B, S, M), E, D, T>, Error = D::Err>,
+    N::Built:
+        Module<Tensor<(B, S, M), E, D, T>, Output = Tensor<(B, S, M), E, D, T>, Error = D::Err>,
+    FF::Built:
+        Module<Tensor<(B, S, M), E, D, T>, Output = Tensor<(B, S, M), E, D, T>, Error = D::Err>,
+    Hd: BuildOnDevice<E, D>,
+    Hd::Built: Module<(Tensor<(B, S, M), E, D, T>, AttentionMask<B, S, S, E, D>), Error = D::Err>,
+{
+    type Output =
+        <Hd::Built as Module<(Tensor<(B, S, M), E, D, T>, AttentionMask<B, S, S, E, D>)>>::Output;
+    type Error = D::Err;
+
+    /// Batched forward of already embedded tokens with the same `mask` applied to the self
+    /// attention of every block and the head
+    fn try_forward(
+        &self,
+        (x, mask): (Tensor<(B, S, M), E, D, T>, AttentionMask<B, S, S, E, D>),
+    ) -> Result<Self::Output, D::Err> {
+        let mut x = self.pos.try_forward(x)?;
+        for block in self.blocks.iter() {
+            x = block.try_forward_masked(x, mask.clone())?;
+        }
+        self.head.try_forward((x, mask))
+    }
+
+    fn try_forward_mut(
+        &mut self,
+        (x, mask): (Tensor<(B, S, M), E, D, T>, AttentionMask<B, S, S, E, D>),
+    ) -> Result<Self::Output, D::Err> {
+        let mut x = self.pos.try_forward_mut(x)?;
+        for block in self.blocks.iter_mut() {
+            x = block.try_forward_masked_mut(x, mask.clone())?;
+        }
+        self.head.try_forward_mut((x, mask))
+    }
+}
+
+impl<
+        V: Dim,
+        M: Dim,
+        H: Dim,
+        F: Dim,
+        N: std::fmt::Debug,
+        FF: std::fmt::Debug,
+        P: std::fmt::Debug,
+        Hd: std::fmt::Debug,
+        E: Dtype,
+        D: Device<E>,
+        B: Dim,
+        S: Dim,
+        T: Tape<E, D>,
+    > Module<(Tensor<(B, S), usize, D, T>, AttentionMask<B, S, S, E, D>)>
+    for TransformerEncoder<V, M, H, F, N, FF, P, Hd, E, D>
+where
+    Self: Module<(Tensor<(B, S, M), E, D, T>, AttentionMask<B, S, S, E, D>), Error = D::Err>,
+{
+    type Output =
+        <Self as Module<(Tensor<(B, S, M), E, D, T>, AttentionMask<B, S, S, E, D>)>>::Output;
+    type Error = D::Err;
+
+    fn try_forward(
+        &self,
+        (x, mask): (Tensor<(B, S), usize, D, T>, AttentionMask<B, S, S, E, D>),
+    ) -> Result<Self::Output, D::Err> {
+        let x = self.embedding.try_forward(x)?;
+        self.try_forward((x, mask))
+    }
+
+    fn try_forward_mut(
+        &mut self,
+        (x, mask): (Tensor<(B, S), usize, D, T>, AttentionMask<B, S, S, E, D>),
+    ) -> Result<Self::Output, D::Err> {
+        let x = self.embedding.try_forward_mut(x)?;
+        self.try_forward_mut((x, mask))
+    }
+}